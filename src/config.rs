@@ -1,7 +1,9 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use directories::ProjectDirs;
 
+use crate::m163::client::TARGET;
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
 pub struct Config {
     #[serde(default)]
@@ -12,17 +14,121 @@ pub struct Config {
     pub less_usage: bool,
     #[serde(default = "default_volume")]
     pub volume: f32,
+    /// 接口地址, 可指向本地的模拟服务
+    #[serde(default = "default_api_base")]
+    pub api_base: String,
+    /// 代理, 支持 http(s):// 与 socks5(h)://, 为空不走代理
+    #[serde(default)]
+    pub proxy: String,
+    /// 建连超时(秒), 0 取默认值
+    #[serde(default)]
+    pub connect_timeout: u64,
+    /// 单次接口请求超时(秒), 0 取默认值; 只读接口重试时还受 `retry.deadline_ms` 限制
+    #[serde(default)]
+    pub request_timeout: u64,
+    /// 音频下载多久收不到数据算卡住(秒), 0 取默认值
+    #[serde(default)]
+    pub download_timeout: u64,
+    /// 只读接口的重试策略
+    #[serde(default)]
+    pub retry: Retry,
+    /// 音质: standard, higher, exhaust, lossless
+    #[serde(default)]
+    pub quality: Quality,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+    #[default]
+    Standard,
+    Higher,
+    Exhaust,
+    Lossless,
+}
+
+impl Quality {
+    /// song/enhance/player/url/v1 的 level 参数
+    pub fn level(&self) -> &'static str {
+        match self {
+            Quality::Standard => "standard",
+            Quality::Higher => "higher",
+            Quality::Exhaust => "exhigh",
+            Quality::Lossless => "lossless",
+        }
+    }
+
+    /// 缓存文件名里的音质标记
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quality::Standard => "standard",
+            Quality::Higher => "higher",
+            Quality::Exhaust => "exhaust",
+            Quality::Lossless => "lossless",
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+#[serde(default)]
+pub struct Retry {
+    /// 总尝试次数, 含第一次
+    pub attempts: u32,
+    /// 首次重试前的等待, 之后翻倍并加抖动
+    pub backoff_ms: u64,
+    /// 单次调用(含全部重试)的总时限, 每次尝试取 `request_timeout` 与剩余时间中较小的;
+    /// 小于 attempts × request_timeout 加退避时, 最后一次尝试会被截短
+    pub deadline_ms: u64,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry {
+            attempts: 3,
+            backoff_ms: 300,
+            // 3 次各 10s 的请求超时, 加上两次退避(最多 0.9s), 断网时半分钟内报错
+            deadline_ms: 31_000,
+        }
+    }
 }
 
 fn default_volume() -> f32 {
     1.0
 }
 
+fn default_api_base() -> String {
+    TARGET.to_owned()
+}
+
+fn secs_or(secs: u64, default: u64) -> Duration {
+    Duration::from_secs(if secs == 0 { default } else { secs })
+}
+
 impl Config {
     pub fn Cache(&self) -> PathBuf {
         PathBuf::from(&self.home_dir).join("cache")
     }
 
+    pub fn base_url(&self) -> &str {
+        if self.api_base.is_empty() {
+            TARGET
+        } else {
+            self.api_base.trim_end_matches('/')
+        }
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        secs_or(self.connect_timeout, 10)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        secs_or(self.request_timeout, 10)
+    }
+
+    pub fn download_timeout(&self) -> Duration {
+        secs_or(self.download_timeout, 30)
+    }
+
     pub fn init(&mut self) {
         std::fs::create_dir_all(self.Cache()).expect("touch.cache_dir");
         if self.volume > 1.0 || self.volume < 0.0 {
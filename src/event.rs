@@ -10,8 +10,10 @@ use tokio_util::sync::CancellationToken;
 
 use crate::{
     m163::typ::{
-        MaybeRecommendSong, PlayDetail, PlayItem, PlayList, Profile, RecommendPlayList,
-        SearchResult,
+        AlbumDetail, ArtistAlbums, ArtistDetail, ArtistSongs, Comments, Intelligence, LikeList,
+        MaybeRecommendSong, NewAlbums, PersonalFm, PlayDetail, PlayItem, PlayList, Profile,
+        RecommendPlayList, SearchResult, SearchSuggest, SearchType, SimiPlayList, SimiSong,
+        TopList,
     },
     ui::{footer::Lyric, widgets::tip::Tip},
 };
@@ -43,10 +45,41 @@ pub async fn ui_event_loop(event_tx: Sender<ES>, cancel: CancellationToken) {
 
 #[derive(Debug)]
 pub enum ES {
-    DataSearch(SearchResult),
+    /// 搜索结果: 关键词, 类型, 偏移, 数据
+    DataSearch(String, SearchType, usize, SearchResult),
+    /// 搜索失败: 关键词, 类型, 偏移
+    DataSearchFailed(String, SearchType, usize),
+    /// 搜索建议: 关键词, 数据
+    DataSuggest(String, SearchSuggest),
+    /// 歌手详情: 歌手 id, 数据
+    DataArtist(usize, ArtistDetail),
+    DataArtistSongs(usize, ArtistSongs),
+    /// 专辑详情: 专辑 id, 数据
+    DataAlbum(usize, AlbumDetail),
+    /// 歌手专辑: 歌手 id, 偏移, 数据
+    DataArtistAlbums(usize, usize, ArtistAlbums),
+    /// 歌手专辑加载失败: 歌手 id, 偏移
+    DataArtistAlbumsFailed(usize, usize),
+    /// 评论: 资源 id, 是否热门, 偏移, 数据
+    DataComments(usize, bool, usize, Comments),
+    /// 评论加载失败: 资源 id, 是否热门, 偏移
+    DataCommentsFailed(usize, bool, usize),
+    /// 相似歌曲: 歌曲 id, 数据
+    DataSimiSong(usize, SimiSong),
+    DataSimiPlayList(usize, SimiPlayList),
     DataProfile(Profile),
+    /// 喜欢的歌曲 id, 登录后加载
+    DataLikeList(LikeList),
+    /// 喜欢状态变化: 歌曲 id, 是否喜欢
+    Liked(usize, bool),
+    /// 私人 FM 的一批歌曲
+    DataFm(PersonalFm),
+    /// 心动模式的一批歌曲: 歌单 id, 数据
+    DataIntelligence(usize, Intelligence),
     DataPlayList(PlayList),
     DataRecommendResource(RecommendPlayList),
+    /// 发现: 排行榜与新碟
+    DataDiscover(TopList, NewAlbums),
     DataRecommendSongs(MaybeRecommendSong),
     DataPlayListDetail(PlayDetail),
     RuntimeHead(HeadMenuKey),
@@ -59,6 +92,8 @@ pub enum ES {
     AppState(AppState),
     Tip(Tip),
     ReloadConfig,
+    /// 下载进度: 歌曲 id, 已下载字节, 总字节(服务端没给长度时为 None)
+    Download(usize, u64, Option<u64>),
 }
 
 #[derive(Debug, Clone)]
@@ -80,6 +115,8 @@ pub enum AppState {
 pub enum HeadMenuKey {
     My,
     Maybe,
+    Discover,
+    Fm,
     About,
 }
 
@@ -89,6 +126,10 @@ pub enum PlayMode {
     Single,
     SingleLoop,
     Random,
+    /// 私人 FM, 列表快放完时自动续上
+    Fm,
+    /// 心动模式: 喜欢的歌单 id, 种子歌曲 id, 同样自动续上
+    Intelligence(usize, usize),
 }
 
 #[derive(Debug, Clone)]
@@ -108,7 +149,7 @@ pub enum PlayListOP {
 
 #[derive(Debug, Clone)]
 pub enum PlayState {
-    Play(usize, String),
+    Play(usize, crate::m163::typ::Lyric),
     Start,
     Stop,
     None,
@@ -1,2 +1,7 @@
+pub mod api;
 pub mod client;
+pub mod crypto;
+#[cfg(test)]
+pub mod mock;
+pub mod stream;
 pub mod typ;
@@ -0,0 +1,110 @@
+use std::{path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::m163::{client::NCErr, stream::Progress, typ};
+
+/// ui 与播放任务用到的接口, `Nc` 为网易云的实现, 测试或其它音源可以换成别的实现
+#[async_trait]
+pub trait MusicApi: Send + Sync {
+    // 登录与账号
+    async fn qr_link(&self) -> Result<typ::QRR, NCErr>;
+    async fn qr_wait_login(&self, key: &str, chain: &str) -> Result<typ::QRLogin, NCErr>;
+    async fn profile(&self) -> Result<typ::Profile, NCErr>;
+    async fn logout(&self);
+    fn save_cookie(&self) -> Result<(), NCErr>;
+    fn clear_cookie(&self);
+    fn set_s_device_id(&self, id: &str);
+    fn s_device_id(&self) -> Option<String>;
+
+    // 浏览
+    async fn recommend_resource(&self) -> Result<typ::RecommendPlayList, NCErr>;
+    async fn recommend_songs(&self) -> Result<typ::MaybeRecommendSong, NCErr>;
+    async fn play_list(&self, offset: usize, limit: usize) -> Result<typ::PlayList, NCErr>;
+    /// 按 more 翻完所有页的个人歌单
+    async fn play_list_all(&self) -> Result<typ::PlayList, NCErr>;
+    /// 歌单详情, 歌曲多要分批补齐时, 每补一批前把已有的交给 `partial`
+    async fn play_detail(
+        &self,
+        id: usize,
+        partial: &(dyn Fn(typ::PlayDetail) + Send + Sync),
+    ) -> Result<typ::PlayDetail, NCErr>;
+    async fn search(
+        &self,
+        search: &str,
+        kind: typ::SearchType,
+        offset: usize,
+        limit: usize,
+    ) -> Result<typ::SearchResult, NCErr>;
+    async fn search_suggest(&self, keyword: &str) -> Result<typ::SearchSuggest, NCErr>;
+    async fn album(&self, id: usize) -> Result<typ::AlbumDetail, NCErr>;
+    async fn artist(&self, id: usize) -> Result<typ::ArtistDetail, NCErr>;
+    /// 歌手最热门的 50 首
+    async fn artist_songs(&self, id: usize) -> Result<typ::ArtistSongs, NCErr>;
+    async fn artist_albums(
+        &self,
+        id: usize,
+        offset: usize,
+        limit: usize,
+    ) -> Result<typ::ArtistAlbums, NCErr>;
+    fn clear_play(&self) -> Result<(), NCErr>;
+    fn clear_play_list(&self, id: usize) -> Result<(), NCErr>;
+
+    // 歌单管理
+    async fn create_play_list(&self, name: &str) -> Result<typ::Any, NCErr>;
+    async fn delete_play_list(&self, id: usize) -> Result<typ::Any, NCErr>;
+    async fn sub_play(&self, id: usize) -> Result<typ::Any, NCErr>;
+    async fn unsub_play(&self, id: usize) -> Result<typ::Any, NCErr>;
+    async fn track(&self, add: bool, play_id: usize, songs: Vec<usize>) -> Result<typ::Any, NCErr>;
+    /// 喜欢/取消喜欢歌曲
+    async fn like(&self, id: usize, like: bool) -> Result<typ::Any, NCErr>;
+    async fn like_list(&self) -> Result<typ::LikeList, NCErr>;
+
+    // 私人 FM
+    async fn fm(&self) -> Result<typ::PersonalFm, NCErr>;
+    /// 不喜欢, 以后不再推荐
+    async fn fm_trash(&self, id: usize) -> Result<typ::Any, NCErr>;
+    /// 排行榜目录, 按天缓存
+    async fn top_list(&self) -> Result<typ::TopList, NCErr>;
+    /// 新歌速递, 按天缓存
+    async fn new_songs(&self) -> Result<typ::NewSongs, NCErr>;
+    /// 新碟上架, 按天缓存
+    async fn new_albums(&self) -> Result<typ::NewAlbums, NCErr>;
+    /// 最新评论, before 为上一页最后一条的时间, 第一页传 0
+    async fn comments(
+        &self,
+        kind: typ::CommentType,
+        id: usize,
+        offset: usize,
+        before: i64,
+    ) -> Result<typ::Comments, NCErr>;
+    /// 热门评论
+    async fn hot_comments(
+        &self,
+        kind: typ::CommentType,
+        id: usize,
+        offset: usize,
+    ) -> Result<typ::Comments, NCErr>;
+    /// 相似歌曲
+    async fn simi_song(&self, id: usize) -> Result<typ::SimiSong, NCErr>;
+    /// 相似歌单
+    async fn simi_play_list(&self, id: usize) -> Result<typ::SimiPlayList, NCErr>;
+    /// 心动模式: 以喜欢的歌单里的一首为种子推荐
+    async fn intelligence(&self, pid: usize, seed: usize) -> Result<typ::Intelligence, NCErr>;
+
+    // 播放
+    async fn song(&self, id: usize) -> Result<typ::Song, NCErr>;
+    async fn song_url(&self, id: usize) -> Result<typ::SongUrl, NCErr>;
+    async fn lyric(&self, id: usize) -> Result<typ::Lyric, NCErr>;
+    fn song_cached(&self, id: usize) -> bool;
+    /// 当前音质下的缓存文件
+    fn song_file(&self, id: usize) -> Option<PathBuf>;
+    /// 下载到缓存, kind 为接口返回的格式, 过程通过 progress 共享给边下边播的读取端
+    async fn download(
+        &self,
+        url: &str,
+        id: usize,
+        kind: &str,
+        progress: Arc<Progress>,
+    ) -> Result<(), NCErr>;
+}
@@ -3,74 +3,45 @@ use std::{
     collections::HashMap,
     fs::File,
     io::{self, Write},
+    path::{Path, PathBuf},
     rc::Rc,
     str::FromStr,
-    sync::{Arc, mpsc::Sender},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+    },
+    time::{Duration, Instant},
 };
 
-use aes::cipher::{
-    BlockEncryptMut, BlockSizeUser, KeyIvInit,
-    block_padding::{Padding, Pkcs7},
-};
-use base64::{Engine, prelude::BASE64_STANDARD};
+use async_trait::async_trait;
 use chrono::{Datelike, Local};
 use color_eyre::owo_colors::OwoColorize;
-use num_bigint::{BigInt, BigUint};
-use num_traits::Num;
-use rand::rngs::OsRng;
 use reqwest::{
-    Body, Url,
+    Body, StatusCode, Url,
     cookie::{CookieStore, Jar},
     header::{
-        ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, CONNECTION, CONTENT_TYPE, HOST, HeaderMap,
-        HeaderName, HeaderValue, REFERER, SEC_WEBSOCKET_VERSION, USER_AGENT,
+        ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, CONNECTION, CONTENT_RANGE, CONTENT_TYPE, ETAG,
+        HOST, HeaderMap, HeaderName, HeaderValue, IF_RANGE, RANGE, REFERER, SEC_WEBSOCKET_VERSION,
+        USER_AGENT,
     },
 };
 
 use crate::{
-    config::Config,
+    config::{Config, Quality},
     event::ES,
-    m163::{client::cache::COOKIE, typ},
+    m163::{
+        api::MusicApi,
+        client::cache::COOKIE,
+        crypto::WeapiCipher,
+        stream::{EXTS, Progress, ext_of, sniff},
+        typ,
+    },
     ui::widgets::tip::Msg,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-const aes_key: [u8; 16] = [0x42; 16];
-
-type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
-type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
-
-fn pkcs7_padded_len(len: usize, block: usize) -> usize {
-    // 断言block_size是2的幂
-    debug_assert!(block != 0 && (block & (block - 1)) == 0);
-    (len + block) & !(block - 1)
-}
-
-fn rsa_no_padding(text: &[u8], pubkey: &str, modulus: &str) -> String {
-    // 反转字节
-    let mut bytes = text.to_vec();
-    bytes.reverse();
-
-    // 转成 hex，再转大整数
-    let hex_str = hex::encode(bytes);
-    let m = BigUint::from_str_radix(&hex_str, 16).unwrap();
-    let e = BigUint::from_str_radix(pubkey, 16).unwrap();
-    let n = BigUint::from_str_radix(modulus, 16).unwrap();
-
-    // 幂模运算
-    let c = m.modpow(&e, &n);
-
-    // 补零输出（256字符，或根据 modulus 字节数决定）
-    let k = ((n.bits() + 7) / 8) as usize;
-    let mut out = format!("{:x}", c);
-    while out.len() < k * 2 {
-        out = format!("0{}", out);
-    }
-
-    out
-}
-
 #[derive(thiserror::Error, Debug)]
 pub enum NCErr {
     #[error("any error")]
@@ -81,9 +52,55 @@ pub enum NCErr {
     Client(String, String),
     #[error("offline")]
     Offline,
+    /// code 301, 登录态失效
+    #[error("login required: {0}")]
+    LoginRequired(String),
+    /// code 405, 请求过于频繁
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+    /// code -460, 触发风控
+    #[error("risk control: {0}")]
+    RiskControl(String),
+    /// code 404, 资源不存在或已下架
+    #[error("not found: {0}")]
+    NotFound(String),
+}
+
+impl NCErr {
+    /// 按响应里的业务 code 归类, 其它 code 交给各接口自己的结构处理
+    pub fn from_code(code: i64, body: String) -> Option<NCErr> {
+        match code {
+            301 => Some(NCErr::LoginRequired(body)),
+            405 => Some(NCErr::RateLimited(body)),
+            -460 => Some(NCErr::RiskControl(body)),
+            404 => Some(NCErr::NotFound(body)),
+            _ => None,
+        }
+    }
+
+    /// 原始响应体, 方便排查
+    pub fn body(&self) -> Option<&str> {
+        match self {
+            NCErr::Resp(body)
+            | NCErr::LoginRequired(body)
+            | NCErr::RateLimited(body)
+            | NCErr::RiskControl(body)
+            | NCErr::NotFound(body) => Some(body),
+            _ => None,
+        }
+    }
 }
 
 pub const TARGET: &str = "https://music.163.com";
+/// 405 后的首次退避时长, 连续触发时翻倍
+const BACKOFF_BASE: Duration = Duration::from_secs(5);
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+/// 拉取全部个人歌单时每页的个数
+const PLAY_LIST_PAGE: usize = 100;
+/// v3/song/detail 每次请求的歌曲数
+const SONG_BATCH: usize = 500;
+/// 评论每页条数
+const COMMENT_PAGE: usize = 20;
 
 mod cache {
     pub const COOKIE: &str = "cookie.cache";
@@ -93,14 +110,16 @@ pub struct Nc {
     client: reqwest::Client,
     down_client: reqwest::Client,
     // csrf: String,
-    aes_1: Aes128CbcEnc,
-    aes_2: Aes128CbcEnc,
-    aes_key_rsa: String,
+    weapi: WeapiCipher,
     _profile: tokio::sync::RwLock<Option<typ::Profile>>,
     jar: Arc<Jar>,
     url: Url,
     config: Config,
     event_tx: Sender<ES>,
+    // 405 退避: 截止时间与连续次数
+    backoff: Mutex<(Option<Instant>, u32)>,
+    // 已经通知过 ui 重新登录, 避免并发请求重复刷新二维码
+    login_required: AtomicBool,
 }
 
 impl Nc {
@@ -117,12 +136,21 @@ impl Nc {
                 }
             }
         }
-        let mut client = reqwest::Client::builder();
+        let mut client = reqwest::Client::builder()
+            .connect_timeout(c.connect_timeout())
+            .timeout(c.request_timeout());
         client = client.cookie_store(true);
         let jar = Arc::new(reqwest::cookie::Jar::default());
         // let mut csrf = String::default();
-        let uu =
-            Url::parse(TARGET).map_err(|err| NCErr::Client("url".to_owned(), err.to_string()))?;
+        let uu = Url::parse(c.base_url())
+            .map_err(|err| NCErr::Client("url".to_owned(), err.to_string()))?;
+        let host = match (uu.host_str(), uu.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_owned(),
+            _ => return Err(NCErr::Client("url".to_owned(), "no host".to_owned())),
+        };
+        let referer = HeaderValue::from_str(c.base_url())
+            .map_err(|err| NCErr::Client("referer".to_owned(), err.to_string()))?;
         cookie.split(";").for_each(|v| {
             // if let Some((k, vv)) = v.trim().split_once("=") {
             //     if k.eq("__csrf") {
@@ -135,8 +163,19 @@ impl Nc {
         });
         client = client.cookie_provider(jar.clone());
         let mut down_client = reqwest::Client::builder()
+            .connect_timeout(c.connect_timeout())
+            // 只限制两次读之间的间隔, 大文件慢慢下也不会被打断
+            .read_timeout(c.download_timeout())
+            // 音频不压缩, 保证 Range 的偏移就是文件里的字节
+            .no_gzip()
             .cookie_store(true)
             .cookie_provider(jar.clone());
+        if !c.proxy.is_empty() {
+            let proxy = reqwest::Proxy::all(&c.proxy)
+                .map_err(|err| NCErr::Client("proxy".to_owned(), err.to_string()))?;
+            client = client.proxy(proxy.clone());
+            down_client = down_client.proxy(proxy);
+        }
         let mut header = HeaderMap::new();
         header.insert(ACCEPT, HeaderValue::from_static("*/*"));
         header.insert(
@@ -154,18 +193,14 @@ impl Nc {
         );
         header.insert(
             HOST,
-            HeaderValue::from_static(&TARGET[TARGET.rfind('/').expect("invalid url") + 1..]),
+            HeaderValue::from_str(&host)
+                .map_err(|err| NCErr::Client("host".to_owned(), err.to_string()))?,
         );
-        header.insert(REFERER, HeaderValue::from_static(TARGET));
+        header.insert(REFERER, referer.clone());
         header.insert(USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.0.0 Safari/537.36"));
         let mut down_header = HeaderMap::new();
-        down_header.insert(REFERER, HeaderValue::from_static(TARGET));
+        down_header.insert(REFERER, referer);
         down_header.insert(USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.0.0 Safari/537.36"));
-        let rnp = rsa_no_padding(
-            aes_key.as_slice(),
-            "010001",
-            "00e0b509f6259df8642dbc35662901477df22677ec152b5ff68ace615bb7b725152b3ab17a876aea8a5aa76d2e417629ec4ee341f56135fccf695280104e0312ecbda92557c93870114af6c9d05c4f7f0c3685b7a46bee255932575cce10b424d813cfe4875d3e82047b97ddef52741d546b8e289dc6935b3ece0462db0a22b8e7",
-        );
         Ok(Nc {
             url: uu,
             jar: jar.clone(),
@@ -178,75 +213,20 @@ impl Nc {
                 .build()
                 .map_err(|err| NCErr::Client("down-build".to_owned(), err.to_string()))?,
             // csrf: csrf,
-            aes_1: Aes128CbcEnc::new(
-                "0CoJUm6Qyw8W8jud".as_bytes().into(),
-                "0102030405060708".as_bytes().into(),
-            ),
-            aes_2: Aes128CbcEnc::new(&aes_key.into(), "0102030405060708".as_bytes().into()),
-            aes_key_rsa: rnp,
+            weapi: WeapiCipher::default(),
             _profile: tokio::sync::RwLock::new(None),
             config: c,
             event_tx,
+            backoff: Mutex::new((None, 0)),
+            login_required: AtomicBool::new(false),
         })
     }
 
-    pub async fn qr_wait_login(&self, key: &str, chain: &str) -> Result<typ::QRLogin, NCErr> {
-        let req = self
-            .client
-            .post(format!("{}/weapi/login/qrcode/client/login", TARGET));
-        // .header(
-        //     HeaderName::from_static("x-login-chain-id"),
-        //     HeaderValue::from_str(chain).unwrap(),
-        // )
-        // .header(
-        //     HeaderName::from_static("x-loginmethod"),
-        //     HeaderValue::from_static("QrCode"),
-        // )
-        // .header(
-        //     HeaderName::from_static("x-os"),
-        //     HeaderValue::from_static("web"),
-        // )
-        // .header(
-        //     HeaderName::from_static("x-channelsource"),
-        //     HeaderValue::from_static("undefined"),
-        // );
-        self._req(
-            req,
-            json!({
-                "type": 1,
-                "key": key.to_owned(),
-                "noCheckToken": true
-            }),
-        )
-        .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
-    }
-
-    pub async fn qr_link(&self) -> Result<typ::QRR, NCErr> {
-        let req = self
-            .client
-            .post(format!("{}/weapi/login/qrcode/unikey", TARGET));
-        self._req(
-            req,
-            json!({
-                "type": 1,
-                "noCheckToken": true,
-            }),
-        )
-        .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
-    }
-
-    pub async fn logout(&self) {
-        *self._profile.write().await = None;
-        let req = self.client.post(format!("{}/weapi/logout", TARGET));
-        self._req::<typ::Any>(req, json!({}))
-            .await
-            .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()));
-    }
-
-    pub fn clear_cookie(&self) {
-        std::fs::remove_file(self.config.Cache().join(COOKIE));
+    /// 测试里换成可以解密的 RSA 密钥
+    #[cfg(test)]
+    pub fn with_weapi(mut self, weapi: WeapiCipher) -> Self {
+        self.weapi = weapi;
+        self
     }
 
     fn _cache<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>, NCErr> {
@@ -281,8 +261,51 @@ impl Nc {
 
     async fn _req<T: serde::de::DeserializeOwned>(
         &self,
-        mut r: reqwest::RequestBuilder,
-        mut data: serde_json::Value,
+        path: &str,
+        data: serde_json::Value,
+    ) -> Result<T, NCErr> {
+        let ret = self._send(path, &data).await;
+        if matches!(ret, Err(NCErr::Offline)) {
+            self._offline();
+        }
+        ret
+    }
+
+    /// 只读接口用, 连不上或超时按 `config.retry` 重试, 全部失败才算离线
+    async fn _req_retry<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        data: serde_json::Value,
+    ) -> Result<T, NCErr> {
+        let policy = &self.config.retry;
+        let deadline = Instant::now() + Duration::from_millis(policy.deadline_ms);
+        let mut attempt = 1;
+        loop {
+            let ret = tokio::time::timeout_at(deadline.into(), self._send(path, &data))
+                .await
+                .unwrap_or(Err(NCErr::Offline));
+            if !matches!(ret, Err(NCErr::Offline)) {
+                return ret;
+            }
+            let wait = retry_wait(policy.backoff_ms, attempt);
+            if attempt >= policy.attempts || Instant::now() + wait >= deadline {
+                self._offline();
+                return ret;
+            }
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    fn _offline(&self) {
+        self.event_tx
+            .send(ES::AppState(crate::event::AppState::Offline));
+    }
+
+    async fn _send<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        data: &serde_json::Value,
     ) -> Result<T, NCErr> {
         // if !self.csrf.is_empty() {
         //     match &mut data {
@@ -295,48 +318,22 @@ impl Nc {
         //         _ => return Err(NCErr::Client("not object".to_owned(), "".to_owned())),
         //     }
         // }
-        let mut m1 = serde_json::to_vec(&data).map_err(|_| NCErr::Any)?;
-        let mut mll = m1.len();
-        m1.resize(pkcs7_padded_len(m1.len(), aes::Aes128::block_size()), 0);
-        self.aes_1
-            .clone()
-            .encrypt_padded_mut::<Pkcs7>(&mut m1, mll)
-            .map_err(|err| NCErr::Client("aes1".to_owned(), format!("{:?}jjjj", err).to_owned()))?;
-        let mut buf = Vec::new();
-        buf.resize(m1.len() * 4 / 3 + 4, 0);
-        let size = BASE64_STANDARD
-            .encode_slice(&m1, &mut buf)
-            .map_err(|err| NCErr::Client("base1".to_owned(), err.to_string()))?;
-        buf.truncate(size);
-        let tmp = m1;
-        m1 = buf;
-        buf = tmp;
-        mll = m1.len();
-        m1.resize(pkcs7_padded_len(m1.len(), aes::Aes128::block_size()), 0);
-        self.aes_2
-            .clone()
-            .encrypt_padded_mut::<Pkcs7>(&mut m1, mll)
-            .map_err(|err| NCErr::Client("aes2".to_owned(), err.to_string()))?;
-        buf.fill(0);
-        buf.resize(m1.len() * 4 / 3 + 4, 0);
-        let size = BASE64_STANDARD
-            .encode_slice(m1, &mut buf)
-            .map_err(|err| NCErr::Client("base2".to_owned(), err.to_string()))?;
-        buf.truncate(size);
-        r = r.form(&HashMap::from([
-            ("params", unsafe {
-                str::from_utf8_unchecked(buf.as_slice())
-            }),
-            ("encSecKey", self.aes_key_rsa.as_str()),
-        ]));
+        self._wait_backoff().await;
+        let text = serde_json::to_vec(data).map_err(|_| NCErr::Any)?;
+        let form = self.weapi.encrypt(&text)?;
+        let mut r = self
+            .client
+            .post(format!("{}/weapi/{}", self.config.base_url(), path))
+            .form(&HashMap::from([
+                ("params", form.params.as_str()),
+                ("encSecKey", form.enc_sec_key.as_str()),
+            ]));
         // if self.csrf.len() > 0 {
         //     r = r.query(&[("csrf_token", self.csrf.as_str())]);
         // }
 
         let resp = r.send().await.map_err(|err| {
             if err.is_timeout() || err.is_connect() {
-                self.event_tx
-                    .send(ES::AppState(crate::event::AppState::Offline));
                 NCErr::Offline
             } else {
                 NCErr::Client("req".to_owned(), err.to_string())
@@ -348,6 +345,18 @@ impl Nc {
             .await
             .map_err(|err| NCErr::Resp(err.to_string()))?;
 
+        if let Some(code) = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|v| v.get("code").and_then(|c| c.as_i64()))
+        {
+            if let Some(err) = NCErr::from_code(code, text.clone()) {
+                self._on_code_err(&err).await;
+                return Err(err);
+            }
+        }
+        self.login_required.store(false, Ordering::Relaxed);
+        self.backoff.lock().unwrap().1 = 0;
+
         if !status.is_success() || text.len() == 0 {
             return Err(NCErr::Resp(text));
         }
@@ -359,6 +368,249 @@ impl Nc {
         })
     }
 
+    async fn _wait_backoff(&self) {
+        let until = self.backoff.lock().unwrap().0;
+        if let Some(until) = until {
+            tokio::time::sleep_until(until.into()).await;
+        }
+    }
+
+    async fn _on_code_err(&self, err: &NCErr) {
+        match err {
+            NCErr::LoginRequired(_) => {
+                *self._profile.write().await = None;
+                if !self.login_required.swap(true, Ordering::Relaxed) {
+                    self.event_tx
+                        .send(ES::AppState(crate::event::AppState::Authing));
+                }
+            }
+            NCErr::RateLimited(_) => {
+                let wait = {
+                    let mut backoff = self.backoff.lock().unwrap();
+                    let wait = BACKOFF_BASE
+                        .saturating_mul(1 << backoff.1.min(4))
+                        .min(BACKOFF_MAX);
+                    backoff.0 = Some(Instant::now() + wait);
+                    backoff.1 += 1;
+                    wait
+                };
+                self.event_tx.send(ES::Tip(Msg(
+                    &format!("请求过于频繁, {}s 后再试", wait.as_secs()),
+                    Duration::from_secs(2),
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    fn _song_path(&self, id: usize, ext: &str) -> PathBuf {
+        self.config
+            .Cache()
+            .join(format!("{}_{}.{}", id, self.config.quality.name(), ext))
+    }
+
+    async fn _download(
+        &self,
+        url: &str,
+        id: usize,
+        part: &Path,
+        progress: &Progress,
+    ) -> Result<(), NCErr> {
+        if let Some(path) = self.song_file(id) {
+            let len = std::fs::metadata(&path).map(|v| v.len()).unwrap_or(0);
+            progress.set_path(path);
+            progress.update(len, Some(len));
+            return Ok(());
+        }
+        self._build().await?;
+
+        // 先写到 .part, 完整下载后再改名, 中途失败不会留下残缺的缓存;
+        // 失败时保留 .part 和 .meta, 下次用 Range 接着下
+        let meta_path = part.with_extension("meta");
+        let meta = std::fs::read(&meta_path)
+            .ok()
+            .and_then(|v| serde_json::from_slice::<PartMeta>(&v).ok());
+        let mut have = std::fs::metadata(part).map(|v| v.len()).unwrap_or(0);
+        let mut resume = meta.filter(|_| have > 0);
+        if resume.is_some()
+            && let Some(ext) = sniff(&read_head(part))
+        {
+            progress.set_path(self._song_path(id, ext));
+        }
+
+        // 服务端确认续传(206)之前不把已有的部分交给读取端,
+        // 回退成 200 时会截断 .part, 不能让读取端先读到旧数据
+        if let Some(m) = resume.as_ref().filter(|m| m.length == Some(have)) {
+            progress.update(have, m.length);
+        } else {
+            let mut resp = self
+                ._download_get(url, resume.as_ref().map(|m| (have, m)))
+                .await?;
+            // 续传被拒(比如没记下长度的 .part 其实已经完整, 服务端给 416),
+            // 丢掉 .part 不带 Range 重下一次, 否则以后每次都会卡在这里
+            if !resp.status().is_success() && resume.is_some() {
+                let _ = std::fs::remove_file(part);
+                let _ = std::fs::remove_file(&meta_path);
+                resume = None;
+                have = 0;
+                resp = self._download_get(url, None).await?;
+            }
+            if !resp.status().is_success() {
+                return Err(NCErr::Resp(format!(
+                    "resp1 status {}",
+                    resp.status().as_str()
+                )));
+            }
+            // 服务端不认 Range 或者文件变了会直接给 200, 从头下载
+            let (offset, total) = match (resp.status(), content_range(&resp)) {
+                (StatusCode::PARTIAL_CONTENT, Some((start, total))) if start == have => {
+                    (have, total.or(resume.as_ref().and_then(|m| m.length)))
+                }
+                _ => (0, resp.content_length()),
+            };
+            let meta = PartMeta {
+                length: total,
+                etag: resp
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_owned()),
+            };
+            std::fs::write(
+                &meta_path,
+                serde_json::to_vec(&meta).map_err(|e| NCErr::Resp(e.to_string()))?,
+            )
+            .map_err(|e| NCErr::Resp(format!("meta {}", e)))?;
+            self._download_to(&mut resp, part, id, offset, total, progress)
+                .await?;
+        }
+        std::fs::rename(part, progress.path()).map_err(|e| NCErr::Resp(format!("rename {}", e)))?;
+        let _ = std::fs::remove_file(&meta_path);
+        Ok(())
+    }
+
+    /// 发起下载请求, range 为续传的起点与上次记下的信息
+    async fn _download_get(
+        &self,
+        url: &str,
+        range: Option<(u64, &PartMeta)>,
+    ) -> Result<reqwest::Response, NCErr> {
+        let mut req = self.down_client.get(url);
+        if let Some((have, m)) = range {
+            req = req.header(RANGE, format!("bytes={}-", have));
+            if let Some(etag) = &m.etag {
+                req = req.header(IF_RANGE, etag);
+            }
+        }
+        req.send().await.map_err(|e| {
+            if e.is_timeout() || e.is_connect() {
+                self.event_tx
+                    .send(ES::AppState(crate::event::AppState::Offline));
+            }
+            NCErr::Resp(format!("resp {}", e))
+        })
+    }
+
+    async fn _download_to(
+        &self,
+        resp: &mut reqwest::Response,
+        part: &Path,
+        id: usize,
+        offset: u64,
+        total: Option<u64>,
+        progress: &Progress,
+    ) -> Result<(), NCErr> {
+        let mut file = if offset > 0 {
+            std::fs::OpenOptions::new().append(true).open(part)
+        } else {
+            File::create(part)
+        }
+        .map_err(|e| NCErr::Resp(format!("create {}", e)))?;
+        let mut got = offset;
+        // 按百分比或每 256K 通知一次, 避免事件太密
+        let mut last = None;
+        progress.update(got, total);
+        self.event_tx.send(ES::Download(id, got, total));
+        while let Some(chunk) = resp
+            .chunk()
+            .await
+            .map_err(|e| NCErr::Resp(format!("chunk {}", e)))?
+        {
+            file.write_all(&chunk)
+                .map_err(|e| NCErr::Resp(format!("write {}", e)))?;
+            if got == 0 {
+                if let Some(ext) = sniff(&chunk) {
+                    progress.set_path(self._song_path(id, ext));
+                }
+            }
+            got += chunk.len() as u64;
+            progress.update(got, total);
+            let step = match total {
+                Some(total) if total > 0 => got * 100 / total,
+                _ => got >> 18,
+            };
+            if last != Some(step) {
+                last = Some(step);
+                self.event_tx.send(ES::Download(id, got, total));
+            }
+        }
+        file.sync_all()
+            .map_err(|e| NCErr::Resp(format!("sync {}", e)))?;
+        if let Some(total) = total.filter(|total| got < *total) {
+            return Err(NCErr::Resp(format!("short body {}/{}", got, total)));
+        }
+        Ok(())
+    }
+
+    /// 详情只带前 1000 首, 其余按 trackIds 分批补齐, 每补一批先把已有的交给 partial
+    async fn _fill_tracks(
+        &self,
+        list: &mut typ::PlayDetailInner,
+        partial: &(dyn Fn(typ::PlayDetail) + Send + Sync),
+    ) -> Result<(), NCErr> {
+        let mut got = list
+            .tracks
+            .iter()
+            .map(|v| (v.id, v.clone()))
+            .collect::<HashMap<_, _>>();
+        let missing = list
+            .track_ids
+            .iter()
+            .map(|v| v.id)
+            .filter(|v| !got.contains_key(v))
+            .collect::<Vec<_>>();
+        for batch in missing.chunks(SONG_BATCH) {
+            partial(typ::PlayDetail {
+                playlist: list.clone(),
+            });
+            let ret: typ::SongDetail = self
+                ._req_retry(
+                    "v3/song/detail",
+                    json!({
+                        "c": format!(
+                            "[{}]",
+                            batch
+                                .iter()
+                                .map(|v| format!(r#"{{"id":{}}}"#, v))
+                                .collect::<Vec<_>>()
+                                .join(",")
+                        ),
+                    }),
+                )
+                .await?;
+            got.extend(ret.songs.into_iter().map(|v| (v.id, v)));
+            // 按 trackIds 的顺序排, 下架等拿不到的歌曲跳过
+            list.tracks = list
+                .track_ids
+                .iter()
+                .filter_map(|v| got.get(&v.id).cloned())
+                .collect();
+        }
+        // 拿不到的也从 id 里去掉, ui 靠两者个数判断是否加载完
+        list.track_ids.retain(|v| got.contains_key(&v.id));
+        Ok(())
+    }
+
     async fn _build(&self) -> Result<(), NCErr> {
         if self._profile.read().await.is_some() {
             return Ok(());
@@ -368,7 +620,105 @@ impl Nc {
         Ok(())
     }
 
-    pub async fn recommend_resource(&self) -> Result<typ::RecommendPlayList, NCErr> {
+    /// 当前用户 id, 期间被 301 清掉登录信息时返回 LoginRequired
+    async fn _uid(&self) -> Result<u64, NCErr> {
+        self._profile
+            .read()
+            .await
+            .as_ref()
+            .map(|v| v.account.id)
+            .ok_or_else(|| NCErr::LoginRequired("".to_owned()))
+    }
+
+    pub fn _clear_cache(&self, key: &str) -> Result<(), NCErr> {
+        std::fs::remove_file(self.config.Cache().join(format!("{}.cache", key)))
+            .map_err(|e| NCErr::Resp(e.to_string()))
+    }
+}
+
+/// 第 n 次失败后的等待: 指数增长, 取 [d/2, d] 之间的随机值避免一起重试
+fn retry_wait(base_ms: u64, attempt: u32) -> Duration {
+    let ms = base_ms.saturating_mul(1 << attempt.saturating_sub(1).min(6));
+    Duration::from_millis(ms / 2 + fastrand::u64(0..=ms / 2))
+}
+
+/// 未下载完的 .part 对应的信息, 用于断点续传
+#[derive(Deserialize, Serialize)]
+struct PartMeta {
+    length: Option<u64>,
+    etag: Option<String>,
+}
+
+// 续传时从已下载部分的文件头识别格式
+fn read_head(path: &Path) -> Vec<u8> {
+    let mut head = vec![0_u8; 16];
+    let n = File::open(path)
+        .and_then(|mut f| io::Read::read(&mut f, &mut head))
+        .unwrap_or(0);
+    head.truncate(n);
+    head
+}
+
+// Content-Range: bytes start-end/total
+fn content_range(resp: &reqwest::Response) -> Option<(u64, Option<u64>)> {
+    let v = resp.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let (range, total) = v.strip_prefix("bytes ")?.split_once('/')?;
+    let start = range.split_once('-')?.0.parse().ok()?;
+    Some((start, total.parse().ok()))
+}
+
+#[async_trait]
+impl MusicApi for Nc {
+    async fn qr_wait_login(&self, key: &str, chain: &str) -> Result<typ::QRLogin, NCErr> {
+        // 网页端还会额外带上这些头
+        // .header(
+        //     HeaderName::from_static("x-login-chain-id"),
+        //     HeaderValue::from_str(chain).unwrap(),
+        // )
+        // .header(
+        //     HeaderName::from_static("x-loginmethod"),
+        //     HeaderValue::from_static("QrCode"),
+        // )
+        // .header(
+        //     HeaderName::from_static("x-os"),
+        //     HeaderValue::from_static("web"),
+        // )
+        // .header(
+        //     HeaderName::from_static("x-channelsource"),
+        //     HeaderValue::from_static("undefined"),
+        // );
+        self._req(
+            "login/qrcode/client/login",
+            json!({
+                "type": 1,
+                "key": key.to_owned(),
+                "noCheckToken": true
+            }),
+        )
+        .await
+    }
+
+    async fn qr_link(&self) -> Result<typ::QRR, NCErr> {
+        self._req(
+            "login/qrcode/unikey",
+            json!({
+                "type": 1,
+                "noCheckToken": true,
+            }),
+        )
+        .await
+    }
+
+    async fn logout(&self) {
+        *self._profile.write().await = None;
+        self._req::<typ::Any>("logout", json!({})).await;
+    }
+
+    fn clear_cookie(&self) {
+        std::fs::remove_file(self.config.Cache().join(COOKIE));
+    }
+
+    async fn recommend_resource(&self) -> Result<typ::RecommendPlayList, NCErr> {
         let now = Local::now();
         let key = &format!(
             "recommend_resource_{}-{:02}-{:02}",
@@ -381,19 +731,14 @@ impl Nc {
         }
         self._build().await?;
 
-        let req = self
-            .client
-            .post(format!("{}/weapi/discovery/recommend/resource", TARGET));
-
         let ret = self
-            ._req(req, json!({}))
-            .await
-            .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))?;
+            ._req_retry("discovery/recommend/resource", json!({}))
+            .await?;
         self._set_cache(key, &ret)?;
         Ok(ret)
     }
 
-    pub async fn recommend_songs(&self) -> Result<typ::MaybeRecommendSong, NCErr> {
+    async fn recommend_songs(&self) -> Result<typ::MaybeRecommendSong, NCErr> {
         let now = Local::now();
         let key = &format!(
             "recommend_{}-{:02}-{:02}",
@@ -406,33 +751,25 @@ impl Nc {
         }
         self._build().await?;
 
-        let req = self
-            .client
-            .post(format!("{}/weapi/v2/discovery/recommend/songs", TARGET));
-
         let ret = self
-            ._req(
-                req,
+            ._req_retry(
+                "v2/discovery/recommend/songs",
                 json!({
                     "offset": 0,
                     "total": true,
                 }),
             )
-            .await
-            .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))?;
+            .await?;
 
         self._set_cache(key, &ret)?;
         Ok(ret)
     }
 
-    pub async fn profile(&self) -> Result<typ::Profile, NCErr> {
-        let req = self
-            .client
-            .post(format!("{}/weapi/w/nuser/account/get", TARGET));
-        self._req(req, json!({})).await
+    async fn profile(&self) -> Result<typ::Profile, NCErr> {
+        self._req_retry("w/nuser/account/get", json!({})).await
     }
 
-    pub fn save_cookie(&self) -> Result<(), NCErr> {
+    fn save_cookie(&self) -> Result<(), NCErr> {
         let cookie = match self.jar.cookies(&self.url) {
             Some(v) => v,
             None => {
@@ -454,12 +791,12 @@ impl Nc {
         Ok(())
     }
 
-    pub fn set_s_device_id(&self, id: &str) {
+    fn set_s_device_id(&self, id: &str) {
         self.jar
             .add_cookie_str(&format!("sDeviceId={}", id), &self.url);
     }
 
-    pub fn s_device_id(&self) -> Option<String> {
+    fn s_device_id(&self) -> Option<String> {
         let cookie = match self.jar.cookies(&self.url) {
             Some(v) => v,
             None => {
@@ -477,103 +814,169 @@ impl Nc {
         None
     }
 
-    pub fn clear_play_list(&self, id: usize) -> Result<(), NCErr> {
+    fn clear_play_list(&self, id: usize) -> Result<(), NCErr> {
         self._clear_cache(&format!("play_detail_{}", id))
     }
-    pub fn _clear_cache(&self, key: &str) -> Result<(), NCErr> {
-        std::fs::remove_file(self.config.Cache().join(format!("{}.cache", key)))
-            .map_err(|e| NCErr::Resp(e.to_string()))
-    }
 
-    pub fn clear_play(&self) -> Result<(), NCErr> {
-        self._clear_cache(cache::PLAY_LIST)
+    fn clear_play(&self) -> Result<(), NCErr> {
+        // 每个分页一个缓存, 连同以前不分页的一起清掉
+        let dir = std::fs::read_dir(self.config.Cache()).map_err(|e| NCErr::Resp(e.to_string()))?;
+        for entry in dir.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with(cache::PLAY_LIST) && name.ends_with(".cache") {
+                std::fs::remove_file(entry.path()).map_err(|e| NCErr::Resp(e.to_string()))?;
+            }
+        }
+        Ok(())
     }
-    pub async fn search(&self, search: &str) -> Result<typ::SearchResult, NCErr> {
+
+    async fn search(
+        &self,
+        search: &str,
+        kind: typ::SearchType,
+        offset: usize,
+        limit: usize,
+    ) -> Result<typ::SearchResult, NCErr> {
         self._build().await?;
-        let req = self
-            .client
-            .post(format!("{}/weapi/cloudsearch/get/web", TARGET));
 
-        self._req(
-            req,
+        self._req_retry(
+            "cloudsearch/get/web",
             json!({
                 "s": search,
-                "offset": "0",
-                "limit": "10",
-                "type": "1",
+                "offset": offset.to_string(),
+                "limit": limit.to_string(),
+                "type": (kind as usize).to_string(),
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
-    pub async fn lyric(&self, id: usize) -> Result<typ::Lyric, NCErr> {
-        let key = &format!("{}.lyric", id);
-        if let Some(ret) = self._cache::<typ::Lyric>(key)? {
+
+    async fn search_suggest(&self, keyword: &str) -> Result<typ::SearchSuggest, NCErr> {
+        // 边输入边请求, 慢了或失败都不影响在线状态, 直接走 _send 且当作没有建议
+        let data = json!({ "s": keyword });
+        let (web, keyword) = tokio::join!(
+            self._send::<typ::SuggestWeb>("search/suggest/web", &data),
+            self._send::<typ::SuggestKeyword>("search/suggest/keyword", &data),
+        );
+        let web = web.map(|v| v.result).unwrap_or_default();
+        Ok(typ::SearchSuggest {
+            keywords: keyword
+                .map(|v| v.result.all_match.into_iter().map(|v| v.keyword).collect())
+                .unwrap_or_default(),
+            artists: web.artists,
+            songs: web.songs,
+        })
+    }
+
+    async fn album(&self, id: usize) -> Result<typ::AlbumDetail, NCErr> {
+        let key = &format!("album_{}", id);
+        if let Some(ret) = self._cache::<typ::AlbumDetail>(key)? {
             return Ok(ret);
         }
         self._build().await?;
-        let req = self.client.post(format!("{}/weapi/song/lyric", TARGET));
 
         let ret = self
-            ._req(
-                req,
+            ._req_retry(&format!("v1/album/{}", id), json!({}))
+            .await?;
+        self._set_cache(key, &ret)?;
+        Ok(ret)
+    }
+
+    async fn artist(&self, id: usize) -> Result<typ::ArtistDetail, NCErr> {
+        self._build().await?;
+
+        self._req_retry(&format!("v1/artist/{}", id), json!({}))
+            .await
+    }
+
+    async fn artist_songs(&self, id: usize) -> Result<typ::ArtistSongs, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            "artist/top/song",
+            json!({
+                "id": id.to_string(),
+            }),
+        )
+        .await
+    }
+
+    async fn artist_albums(
+        &self,
+        id: usize,
+        offset: usize,
+        limit: usize,
+    ) -> Result<typ::ArtistAlbums, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            &format!("artist/albums/{}", id),
+            json!({
+                "offset": offset,
+                "limit": limit,
+                "total": true,
+            }),
+        )
+        .await
+    }
+
+    async fn lyric(&self, id: usize) -> Result<typ::Lyric, NCErr> {
+        let key = &format!("{}.lyric", id);
+        // 旧缓存没存翻译, 重新取一次; 新缓存没有翻译时存的是空的
+        if let Some(ret) = self._cache::<typ::Lyric>(key)?
+            && ret.tlyric.is_some()
+        {
+            return Ok(ret);
+        }
+        self._build().await?;
+
+        let mut ret: typ::Lyric = self
+            ._req_retry(
+                "song/lyric",
                 json!({
                     "id": id.to_string(),
                     "tv": -1,
                     "lv": -1,
                 }),
             )
-            .await
-            .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))?;
-
+            .await?;
+        ret.tlyric.get_or_insert(typ::LyricInner {
+            lyric: String::new(),
+        });
         self._set_cache(key, &ret)?;
         Ok(ret)
     }
 
-    pub async fn create_play_list(&self, name: &str) -> Result<typ::Any, NCErr> {
+    async fn create_play_list(&self, name: &str) -> Result<typ::Any, NCErr> {
         self._build().await?;
-        let req = self
-            .client
-            .post(format!("{}/weapi/playlist/create", TARGET));
 
         self._req(
-            req,
+            "playlist/create",
             json!({
                 "name": name,
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
-    pub async fn delete_play_list(&self, id: usize) -> Result<typ::Any, NCErr> {
+
+    async fn delete_play_list(&self, id: usize) -> Result<typ::Any, NCErr> {
         self._build().await?;
-        let req = self
-            .client
-            .post(format!("{}/weapi/playlist/delete", TARGET));
 
         self._req(
-            req,
+            "playlist/delete",
             json!({
                 "pid": id.to_string(),
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
 
-    pub async fn track(
-        &self,
-        add: bool,
-        play_id: usize,
-        songs: Vec<usize>,
-    ) -> Result<typ::Any, NCErr> {
+    async fn track(&self, add: bool, play_id: usize, songs: Vec<usize>) -> Result<typ::Any, NCErr> {
         self._build().await?;
-        let req = self
-            .client
-            .post(format!("{}/weapi/playlist/manipulate/tracks", TARGET));
 
         self._req(
-            req,
+            "playlist/manipulate/tracks",
             json!({
                 "trackIds": format!("[{}]", songs.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")),
                 "pid": play_id.to_string(),
@@ -581,77 +984,278 @@ impl Nc {
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
 
-    pub async fn play_list(&self, offset: usize, limit: usize) -> Result<typ::PlayList, NCErr> {
-        if let Some(ret) = self._cache::<typ::PlayList>(cache::PLAY_LIST)? {
+    async fn like(&self, id: usize, like: bool) -> Result<typ::Any, NCErr> {
+        self._build().await?;
+
+        self._req(
+            "radio/like",
+            json!({
+                "alg": "itembased",
+                "trackId": id.to_string(),
+                "like": like,
+                "time": "3",
+            }),
+        )
+        .await
+    }
+
+    async fn like_list(&self) -> Result<typ::LikeList, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            "song/like/get",
+            json!({
+                "uid": self._uid().await?,
+            }),
+        )
+        .await
+    }
+
+    async fn fm(&self) -> Result<typ::PersonalFm, NCErr> {
+        self._build().await?;
+
+        self._req_retry("v1/radio/get", json!({})).await
+    }
+
+    async fn fm_trash(&self, id: usize) -> Result<typ::Any, NCErr> {
+        self._build().await?;
+
+        self._req(
+            "radio/trash/add",
+            json!({
+                "alg": "RT",
+                "songId": id,
+                "time": 25,
+            }),
+        )
+        .await
+    }
+
+    async fn top_list(&self) -> Result<typ::TopList, NCErr> {
+        let now = Local::now();
+        let key = &format!(
+            "top_list_{}-{:02}-{:02}",
+            now.year(),
+            now.month(),
+            now.day()
+        );
+        if let Some(ret) = self._cache::<typ::TopList>(key)? {
+            return Ok(ret);
+        }
+        self._build().await?;
+
+        let ret = self._req_retry("toplist", json!({})).await?;
+        self._set_cache(key, &ret)?;
+        Ok(ret)
+    }
+
+    async fn new_songs(&self) -> Result<typ::NewSongs, NCErr> {
+        let now = Local::now();
+        let key = &format!(
+            "new_songs_{}-{:02}-{:02}",
+            now.year(),
+            now.month(),
+            now.day()
+        );
+        if let Some(ret) = self._cache::<typ::NewSongs>(key)? {
+            return Ok(ret);
+        }
+        self._build().await?;
+
+        let ret = self
+            ._req_retry(
+                "v1/discovery/new/songs",
+                json!({ "areaId": 0, "total": true }),
+            )
+            .await?;
+        self._set_cache(key, &ret)?;
+        Ok(ret)
+    }
+
+    async fn new_albums(&self) -> Result<typ::NewAlbums, NCErr> {
+        let now = Local::now();
+        let key = &format!(
+            "new_albums_{}-{:02}-{:02}",
+            now.year(),
+            now.month(),
+            now.day()
+        );
+        if let Some(ret) = self._cache::<typ::NewAlbums>(key)? {
             return Ok(ret);
         }
         self._build().await?;
 
-        let req = self.client.post(format!("{}/weapi/user/playlist", TARGET));
+        let ret = self
+            ._req_retry(
+                "album/new",
+                json!({ "area": "ALL", "offset": 0, "limit": 30, "total": true }),
+            )
+            .await?;
+        self._set_cache(key, &ret)?;
+        Ok(ret)
+    }
+
+    async fn comments(
+        &self,
+        kind: typ::CommentType,
+        id: usize,
+        offset: usize,
+        before: i64,
+    ) -> Result<typ::Comments, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            &format!("v1/resource/comments/{}", kind.thread(id)),
+            json!({
+                "rid": id,
+                "offset": offset,
+                "limit": COMMENT_PAGE,
+                "beforeTime": before,
+            }),
+        )
+        .await
+    }
+
+    async fn hot_comments(
+        &self,
+        kind: typ::CommentType,
+        id: usize,
+        offset: usize,
+    ) -> Result<typ::Comments, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            &format!("v1/resource/hotcomments/{}", kind.thread(id)),
+            json!({
+                "rid": id,
+                "offset": offset,
+                "limit": COMMENT_PAGE,
+                "beforeTime": 0,
+            }),
+        )
+        .await
+    }
+
+    async fn simi_song(&self, id: usize) -> Result<typ::SimiSong, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            "v1/discovery/simiSong",
+            json!({
+                "songid": id,
+                "offset": 0,
+                "limit": 50,
+            }),
+        )
+        .await
+    }
+
+    async fn simi_play_list(&self, id: usize) -> Result<typ::SimiPlayList, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            "discovery/simiPlaylist",
+            json!({
+                "songid": id,
+                "offset": 0,
+                "limit": 50,
+            }),
+        )
+        .await
+    }
+
+    async fn intelligence(&self, pid: usize, seed: usize) -> Result<typ::Intelligence, NCErr> {
+        self._build().await?;
+
+        self._req_retry(
+            "playmode/intelligence/list",
+            json!({
+                "songId": seed,
+                "type": "fromPlayOne",
+                "playlistId": pid,
+                "startMusicId": seed,
+                "count": 1,
+            }),
+        )
+        .await
+    }
+
+    async fn play_list(&self, offset: usize, limit: usize) -> Result<typ::PlayList, NCErr> {
+        let key = &format!("{}_{}_{}", cache::PLAY_LIST, offset, limit);
+        if let Some(ret) = self._cache::<typ::PlayList>(key)? {
+            return Ok(ret);
+        }
+        self._build().await?;
 
         let ret = self
-            ._req(
-                req,
+            ._req_retry(
+                "user/playlist",
                 json!({
-                    "uid": self._profile.read().await.as_ref().unwrap().account.id,
+                    "uid": self._uid().await?,
                     "offset": offset,
                     "limit": limit,
                 }),
             )
-            .await
-            .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))?;
-        self._set_cache(cache::PLAY_LIST, &ret)?;
+            .await?;
+        self._set_cache(key, &ret)?;
         Ok(ret)
     }
 
-    pub async fn sub_play(&self, id: usize) -> Result<typ::Any, NCErr> {
+    async fn play_list_all(&self) -> Result<typ::PlayList, NCErr> {
+        let mut ret = typ::PlayList {
+            more: false,
+            list: vec![],
+        };
+        loop {
+            let page = self.play_list(ret.list.len(), PLAY_LIST_PAGE).await?;
+            let done = !page.more || page.list.is_empty();
+            ret.list.extend(page.list);
+            if done {
+                return Ok(ret);
+            }
+        }
+    }
+
+    async fn sub_play(&self, id: usize) -> Result<typ::Any, NCErr> {
         self._build().await?;
-        let req = self
-            .client
-            .post(format!("{}/weapi/playlist/subscribe", TARGET));
 
         self._req(
-            req,
+            "playlist/subscribe",
             json!({
                 "id": id,
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
 
-    pub async fn unsub_play(&self, id: usize) -> Result<typ::Any, NCErr> {
+    async fn unsub_play(&self, id: usize) -> Result<typ::Any, NCErr> {
         self._build().await?;
-        let req = self
-            .client
-            .post(format!("{}/weapi/playlist/unsubscribe", TARGET));
 
         self._req(
-            req,
+            "playlist/unsubscribe",
             json!({
                 "id": id,
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
-    pub async fn play_detail(&self, id: usize) -> Result<typ::PlayDetail, NCErr> {
+
+    async fn play_detail(
+        &self,
+        id: usize,
+        partial: &(dyn Fn(typ::PlayDetail) + Send + Sync),
+    ) -> Result<typ::PlayDetail, NCErr> {
         let key = &format!("play_detail_{}", id);
         if let Some(ret) = self._cache::<typ::PlayDetail>(key)? {
             return Ok(ret);
         }
         self._build().await?;
 
-        let req = self
-            .client
-            .post(format!("{}/weapi/v6/playlist/detail", TARGET));
-
-        let ret = self
-            ._req(
-                req,
+        let mut ret: typ::PlayDetail = self
+            ._req_retry(
+                "v6/playlist/detail",
                 json!({
                     "id": id,
                     "total": true,
@@ -660,91 +1264,71 @@ impl Nc {
                     "offset": 0,
                 }),
             )
-            .await
-            .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))?;
+            .await?;
+        self._fill_tracks(&mut ret.playlist, partial).await?;
         self._set_cache(key, &ret)?;
         Ok(ret)
     }
 
-    pub async fn song_url(&self, id: usize) -> Result<typ::SongUrl, NCErr> {
+    async fn song_url(&self, id: usize) -> Result<typ::SongUrl, NCErr> {
         self._build().await?;
 
-        let req = self
-            .client
-            .post(format!("{}/weapi/song/enhance/player/url", TARGET));
-
-        self._req(
-            req,
+        self._req_retry(
+            "song/enhance/player/url/v1",
             json!({
                 "ids": format!("[{}]", id),
-                "br": 128000,
+                "level": self.config.quality.level(),
+                "encodeType": "flac",
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
 
-    pub async fn song(&self, id: usize) -> Result<typ::Song, NCErr> {
+    async fn song(&self, id: usize) -> Result<typ::Song, NCErr> {
         self._build().await?;
 
-        let req = self.client.post(format!("{}/weapi/song/detail", TARGET));
-
-        self._req(
-            req,
+        self._req_retry(
+            "song/detail",
             json!({
                 "ids": format!("[{}]", id),
             }),
         )
         .await
-        .map_err(|err| NCErr::Client("req".to_owned(), err.to_string()))
     }
 
-    pub fn song_cached(&self, id: usize) -> bool {
-        let path = self.config.Cache().join(format!("{}.mp3", id));
-        std::fs::metadata(&path).is_ok()
+    fn song_cached(&self, id: usize) -> bool {
+        self.song_file(id).is_some()
     }
 
-    pub async fn download(&self, url: &str, id: usize) -> Result<(), NCErr> {
-        let path = self.config.Cache().join(format!("{}.mp3", id));
-        if let Ok(_) = std::fs::metadata(&path) {
-            return Ok(());
+    fn song_file(&self, id: usize) -> Option<PathBuf> {
+        let mut names = EXTS
+            .iter()
+            .map(|ext| self._song_path(id, ext))
+            .collect::<Vec<_>>();
+        // 之前只有 128k 的 mp3, 文件名不带音质
+        if self.config.quality == Quality::Standard {
+            names.push(self.config.Cache().join(format!("{}.mp3", id)));
         }
-        self._build().await?;
-        let mut resp = match self.down_client.get(url).send().await {
-            Ok(r) => r,
-            Err(e) => {
-                if e.is_timeout() || e.is_connect() {
-                    self.event_tx
-                        .send(ES::AppState(crate::event::AppState::Offline));
-                }
-                return Err(NCErr::Resp(format!("resp {}", e)));
-            }
-        };
+        names.into_iter().find(|v| std::fs::metadata(v).is_ok())
+    }
 
-        if !resp.status().is_success() {
-            return Err(NCErr::Resp(format!(
-                "resp1 status {}",
-                resp.status().as_str()
-            )));
-        }
-        let mut file = match File::create(&path) {
-            Ok(file) => file,
-            Err(e) => {
-                return Err(NCErr::Resp(format!("resp2 {}", e)));
-            }
-        };
-        let bytes = match resp.bytes().await {
-            Ok(r) => r,
-            Err(e) => {
-                return Err(NCErr::Resp(format!("resp3 {}", e)));
-            }
-        }; // 一次性读取全部内容
-        match file.write_all(&bytes) {
-            Ok(r) => {}
-            Err(e) => {
-                return Err(NCErr::Resp(format!("resp4 {}", e)));
-            }
-        };
-        Ok(())
+    async fn download(
+        &self,
+        url: &str,
+        id: usize,
+        kind: &str,
+        progress: Arc<Progress>,
+    ) -> Result<(), NCErr> {
+        let part = self
+            .config
+            .Cache()
+            .join(format!("{}_{}.part", id, self.config.quality.name()));
+        progress.start(part.clone(), self._song_path(id, ext_of(kind)));
+        let ret = self._download(url, id, &part, &progress).await;
+        progress.finish(ret.is_ok());
+        ret
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,9 @@
+mod account;
+mod comment;
+mod discover;
+mod download;
+mod errors;
+mod library;
+mod play_list;
+mod radio;
+mod search;
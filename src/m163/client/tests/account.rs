@@ -0,0 +1,37 @@
+use crate::m163::{api::MusicApi, mock::setup};
+
+#[tokio::test]
+async fn login_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let link = nc.qr_link().await.unwrap();
+    assert_eq!(link.unikey, "mock-unikey");
+    assert_eq!(mock.requests("login/qrcode/unikey")[0].params["type"], 1);
+
+    let login = nc.qr_wait_login(&link.unikey, "").await.unwrap();
+    assert_eq!(login.code, 803);
+    assert_eq!(
+        mock.requests("login/qrcode/client/login")[0].params["key"],
+        "mock-unikey"
+    );
+
+    let profile = nc.profile().await.unwrap();
+    assert_eq!(profile.account.id, 10001);
+    assert_eq!(profile.profile.nickname, "mock-user");
+}
+
+#[tokio::test]
+async fn like_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let list = nc.like_list().await.unwrap();
+    assert_eq!(list.ids, vec![3001, 3002]);
+    assert_eq!(mock.requests("song/like/get")[0].params["uid"], 10001);
+
+    nc.like(3003, true).await.unwrap();
+    nc.like(3001, false).await.unwrap();
+    let reqs = mock.requests("radio/like");
+    assert_eq!(reqs[0].params["trackId"], "3003");
+    assert_eq!(reqs[0].params["like"], true);
+    assert_eq!(reqs[1].params["like"], false);
+}
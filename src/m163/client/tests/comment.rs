@@ -0,0 +1,30 @@
+use crate::m163::{api::MusicApi, mock::setup, typ::CommentType};
+
+#[tokio::test]
+async fn comments_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let ret = nc.comments(CommentType::Song, 3001, 0, 0).await.unwrap();
+    assert_eq!(ret.total, 3);
+    assert!(ret.more);
+    assert_eq!(ret.hot_comments.len(), 1);
+    assert_eq!(ret.comments[1].user.nickname, "听歌的人");
+    assert_eq!(ret.comments[1].liked_count, 2);
+
+    // 下一页带上游标
+    let before = ret.comments[1].time;
+    nc.comments(CommentType::Song, 3001, 2, before)
+        .await
+        .unwrap();
+    let req = &mock.requests("v1/resource/comments/R_SO_4_3001")[1];
+    assert_eq!(req.params["offset"], 2);
+    assert_eq!(req.params["beforeTime"], before);
+
+    let hot = nc
+        .hot_comments(CommentType::PlayList, 2001, 0)
+        .await
+        .unwrap();
+    assert!(!hot.more);
+    assert_eq!(hot.hot_comments[0].comment_id, 9003);
+    assert!(hot.comments.is_empty());
+}
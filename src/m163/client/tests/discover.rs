@@ -0,0 +1,27 @@
+use crate::m163::{api::MusicApi, mock::setup, typ::PlayItem};
+
+#[tokio::test]
+async fn discover_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let top = nc.top_list().await.unwrap();
+    assert_eq!(top.list.len(), 2);
+    assert_eq!(top.list[0].id, 19723756);
+    let songs = nc.new_songs().await.unwrap();
+    let item: PlayItem = songs.data.into_iter().next().unwrap().into();
+    assert_eq!(item.id, 3005);
+    let albums = nc.new_albums().await.unwrap();
+    assert_eq!(albums.albums[0].id, 5004);
+    assert_eq!(
+        albums.albums[0].artist.as_ref().unwrap().name.as_deref(),
+        Some("周杰伦")
+    );
+
+    // 当天再取走缓存
+    nc.top_list().await.unwrap();
+    nc.new_songs().await.unwrap();
+    nc.new_albums().await.unwrap();
+    assert_eq!(mock.requests("toplist").len(), 1);
+    assert_eq!(mock.requests("v1/discovery/new/songs").len(), 1);
+    assert_eq!(mock.requests("album/new").len(), 1);
+}
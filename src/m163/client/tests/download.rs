@@ -0,0 +1,242 @@
+use tokio::net::TcpListener;
+
+use crate::{
+    config::Quality,
+    event::ES,
+    m163::{
+        api::MusicApi,
+        mock::{AUDIO, MockServer, Reply, setup},
+        stream::Progress,
+    },
+};
+
+#[tokio::test]
+async fn play_flow() {
+    let (mock, nc, rx) = setup().await;
+
+    let lyric = nc.lyric(3001).await.unwrap();
+    assert!(lyric.lrc.lyric.contains("故事的小黄花"));
+    assert!(
+        lyric
+            .tlyric
+            .as_ref()
+            .unwrap()
+            .lyric
+            .contains("yellow flower")
+    );
+    assert!(lyric.romalrc.as_ref().unwrap().lyric.is_empty());
+
+    assert!(!nc.song_cached(3001));
+    let url = nc.song_url(3001).await.unwrap();
+    assert_eq!(
+        url.data[0].url,
+        format!("{}/audio/silence.mp3", mock.base())
+    );
+    let progress = Progress::new();
+    assert_eq!(
+        mock.requests("song/enhance/player/url/v1")[0].params["level"],
+        "standard"
+    );
+    nc.download(&url.data[0].url, 3001, "mp3", progress.clone())
+        .await
+        .unwrap();
+    assert!(nc.song_cached(3001));
+
+    let file = mock.config().Cache().join("3001_standard.mp3");
+    assert_eq!(nc.song_file(3001), Some(file.clone()));
+    assert_eq!(std::fs::read(&file).unwrap(), AUDIO);
+    assert!(!mock.config().Cache().join("3001_standard.part").exists());
+    let last = rx
+        .try_iter()
+        .filter_map(|v| match v {
+            ES::Download(id, got, total) => Some((id, got, total)),
+            _ => None,
+        })
+        .last();
+    let size = AUDIO.len() as u64;
+    assert_eq!(last, Some((3001, size, Some(size))));
+    assert!(rodio::Decoder::try_from(std::fs::File::open(&file).unwrap()).is_ok());
+    // 下载完成后边下边播的读取端拿到的是缓存文件
+    assert!(progress.wait(u64::MAX));
+    assert_eq!(progress.total(), Some(AUDIO.len() as u64));
+    assert!(
+        rodio::Decoder::builder()
+            .with_data(progress.open().unwrap())
+            .with_byte_len(AUDIO.len() as u64)
+            .build()
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn stalled_download_times_out() {
+    let mock = MockServer::start().await;
+    // 只建连不回包
+    let stalled = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/audio.mp3", stalled.local_addr().unwrap());
+    let hold = tokio::spawn(async move {
+        let mut conns = vec![];
+        while let Ok((stream, _)) = stalled.accept().await {
+            conns.push(stream);
+        }
+    });
+    let mut c = mock.config();
+    c.download_timeout = 1;
+    let (nc, _rx) = mock.nc_with(c);
+
+    let st = std::time::Instant::now();
+    let progress = Progress::new();
+    assert!(
+        nc.download(&url, 3001, "mp3", progress.clone())
+            .await
+            .is_err()
+    );
+    assert!(!progress.wait(1));
+    assert!(st.elapsed() < std::time::Duration::from_secs(5));
+    assert!(!nc.song_cached(3001));
+    hold.abort();
+}
+
+#[tokio::test]
+async fn quality_and_format() {
+    let mock = MockServer::start().await;
+    let mut c = mock.config();
+    c.quality = Quality::Lossless;
+    let (nc, _rx) = mock.nc_with(c);
+    mock.set(
+        "song/enhance/player/url/v1",
+        Reply::json(&format!(
+            r#"{{"code":200,"data":[{{"id":3001,"url":"{}/audio/3001.flac","br":999000,"code":200,"time":1045,"type":"mp3","encodeType":"flac","level":"lossless"}}]}}"#,
+            mock.base()
+        )),
+    );
+    let mut flac = b"fLaC".to_vec();
+    flac.resize(64, 0);
+    mock.set(
+        "/audio/3001.flac",
+        Reply {
+            status: 200,
+            headers: vec![],
+            body: flac.clone(),
+        },
+    );
+
+    let url = nc.song_url(3001).await.unwrap();
+    assert_eq!(
+        mock.requests("song/enhance/player/url/v1")[0].params["level"],
+        "lossless"
+    );
+    // 接口给的 type 不对时按文件头来
+    nc.download(
+        &url.data[0].url,
+        3001,
+        url.data[0].kind.as_deref().unwrap_or_default(),
+        Progress::new(),
+    )
+    .await
+    .unwrap();
+    let file = mock.config().Cache().join("3001_lossless.flac");
+    assert_eq!(nc.song_file(3001), Some(file.clone()));
+    assert_eq!(std::fs::read(&file).unwrap(), flac);
+
+    // 其它音质不会用到这个缓存
+    let (standard, _rx) = mock.nc();
+    assert!(!standard.song_cached(3001));
+}
+
+#[tokio::test]
+async fn resume_download() {
+    let (mock, nc, _rx) = setup().await;
+    let url = format!("{}/audio/silence.mp3", mock.base());
+    let cache = mock.config().Cache();
+    let part = cache.join("3001_standard.part");
+    let meta = cache.join("3001_standard.meta");
+    let size = AUDIO.len();
+
+    // 上次下到一半
+    std::fs::write(&part, &AUDIO[..1000]).unwrap();
+    std::fs::write(
+        &meta,
+        format!(r#"{{"length":{},"etag":"\"silence\""}}"#, size),
+    )
+    .unwrap();
+    nc.download(&url, 3001, "mp3", Progress::new())
+        .await
+        .unwrap();
+    let reqs = mock.requests("/audio/silence.mp3");
+    assert_eq!(reqs[0].headers["range"], "bytes=1000-");
+    let file = cache.join("3001_standard.mp3");
+    assert_eq!(std::fs::read(&file).unwrap(), AUDIO);
+    assert!(!part.exists() && !meta.exists());
+
+    // 文件变了(ETag 对不上)时服务端给 200, 从头下载
+    std::fs::remove_file(&file).unwrap();
+    std::fs::write(&part, b"stale").unwrap();
+    std::fs::write(&meta, r#"{"length":99,"etag":"\"old\""}"#).unwrap();
+    nc.download(&url, 3001, "mp3", Progress::new())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&file).unwrap(), AUDIO);
+
+    // 不支持 Range 的服务
+    std::fs::remove_file(&file).unwrap();
+    std::fs::write(&part, &AUDIO[..1000]).unwrap();
+    std::fs::write(&meta, format!(r#"{{"length":{}}}"#, size)).unwrap();
+    mock.set(
+        "/audio/plain.mp3",
+        Reply {
+            status: 200,
+            headers: vec![],
+            body: AUDIO.to_vec(),
+        },
+    );
+    nc.download(
+        &format!("{}/audio/plain.mp3", mock.base()),
+        3001,
+        "mp3",
+        Progress::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(&file).unwrap(), AUDIO);
+}
+
+#[tokio::test]
+async fn resume_rejected_range() {
+    let (mock, nc, _rx) = setup().await;
+    let url = format!("{}/audio/silence.mp3", mock.base());
+    let cache = mock.config().Cache();
+    let part = cache.join("3001_standard.part");
+    let meta = cache.join("3001_standard.meta");
+
+    // 第一次没拿到长度, .part 其实已经完整, 续传会被 416 拒掉
+    std::fs::write(&part, AUDIO).unwrap();
+    std::fs::write(&meta, r#"{"length":null,"etag":"\"silence\""}"#).unwrap();
+    nc.download(&url, 3001, "mp3", Progress::new())
+        .await
+        .unwrap();
+    let reqs = mock.requests("/audio/silence.mp3");
+    assert_eq!(reqs.len(), 2);
+    assert_eq!(reqs[0].headers["range"], format!("bytes={}-", AUDIO.len()));
+    // 丢掉 .part 后不带 Range 从头下载
+    assert!(!reqs[1].headers.contains_key("range"));
+    assert_eq!(
+        std::fs::read(cache.join("3001_standard.mp3")).unwrap(),
+        AUDIO
+    );
+    assert!(!part.exists() && !meta.exists());
+}
+
+#[tokio::test]
+async fn lyric_cache() {
+    let (mock, nc, _rx) = setup().await;
+    // 大多数歌没有翻译, 也要走缓存
+    mock.set(
+        "song/lyric",
+        Reply::json(r#"{"code":200,"lrc":{"lyric":"[00:01.00]晴天"}}"#),
+    );
+    let lyric = nc.lyric(3001).await.unwrap();
+    assert!(lyric.tlyric.unwrap().lyric.is_empty());
+    nc.lyric(3001).await.unwrap();
+    assert_eq!(mock.requests("song/lyric").len(), 1);
+}
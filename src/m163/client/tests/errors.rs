@@ -0,0 +1,95 @@
+use std::sync::mpsc;
+
+use crate::{
+    event::ES,
+    m163::{
+        api::MusicApi,
+        client::{NCErr, Nc},
+        mock::{MockServer, Reply, setup},
+        typ::SearchType,
+    },
+};
+
+#[tokio::test]
+async fn error_codes() {
+    let (mock, nc, rx) = setup().await;
+
+    mock.set("w/nuser/account/get", Reply::json(r#"{"code":301}"#));
+    let err = nc.profile().await.unwrap_err();
+    assert!(matches!(err, NCErr::LoginRequired(_)));
+    assert_eq!(err.body(), Some(r#"{"code":301}"#));
+    assert!(nc.profile().await.is_err());
+    // 并发或连续的 301 只通知一次
+    let authing = rx
+        .try_iter()
+        .filter(|v| matches!(v, ES::AppState(crate::event::AppState::Authing)))
+        .count();
+    assert_eq!(authing, 1);
+
+    mock.set(
+        "w/nuser/account/get",
+        Reply::json(include_str!("../../mock/profile.json")),
+    );
+    mock.set(
+        "song/lyric",
+        Reply::json(r#"{"code":-460,"message":"Cheating"}"#),
+    );
+    assert!(matches!(
+        nc.lyric(3001).await.unwrap_err(),
+        NCErr::RiskControl(_)
+    ));
+    mock.set("v6/playlist/detail", Reply::json(r#"{"code":404}"#));
+    assert!(matches!(
+        nc.play_detail(2001, &|_| {}).await.unwrap_err(),
+        NCErr::NotFound(_)
+    ));
+
+    mock.set("cloudsearch/get/web", Reply::json(r#"{"code":405}"#));
+    assert!(matches!(
+        nc.search("晴天", SearchType::Song, 0, 30)
+            .await
+            .unwrap_err(),
+        NCErr::RateLimited(_)
+    ));
+    assert!(rx.try_iter().any(|v| matches!(v, ES::Tip(_))));
+}
+
+#[tokio::test]
+async fn retry_then_offline() {
+    let mock = MockServer::start().await;
+    // 拿一个没人监听的端口
+    let dead = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut c = mock.config();
+    c.api_base = format!("http://{}", dead.local_addr().unwrap());
+    c.retry.backoff_ms = 10;
+    drop(dead);
+    let (tx, rx) = mpsc::channel();
+    let nc = Nc::new(tx, c).unwrap();
+
+    assert!(matches!(nc.profile().await.unwrap_err(), NCErr::Offline));
+    // 重试全部失败后才通知离线, 且只通知一次
+    let offline = rx
+        .try_iter()
+        .filter(|v| matches!(v, ES::AppState(crate::event::AppState::Offline)))
+        .count();
+    assert_eq!(offline, 1);
+}
+
+#[tokio::test]
+async fn suggest_stays_online() {
+    let mock = MockServer::start().await;
+    let dead = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut c = mock.config();
+    c.api_base = format!("http://{}", dead.local_addr().unwrap());
+    drop(dead);
+    let (tx, rx) = mpsc::channel();
+    let nc = Nc::new(tx, c).unwrap();
+
+    // 输入建议连不上也不切到离线
+    let ret = nc.search_suggest("晴").await.unwrap();
+    assert!(ret.songs.is_empty() && ret.keywords.is_empty());
+    assert!(
+        !rx.try_iter()
+            .any(|v| matches!(v, ES::AppState(crate::event::AppState::Offline)))
+    );
+}
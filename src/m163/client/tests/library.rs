@@ -0,0 +1,44 @@
+use crate::m163::{api::MusicApi, mock::setup};
+
+#[tokio::test]
+async fn artist_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let detail = nc.artist(4001).await.unwrap();
+    assert_eq!(detail.artist.name, "周杰伦");
+    assert_eq!(detail.artist.album_size, 2);
+    assert!(detail.artist.brief_desc.is_some());
+
+    let songs = nc.artist_songs(4001).await.unwrap();
+    assert_eq!(songs.songs.len(), 2);
+    assert_eq!(songs.songs[0].art_r[0].id, 4001);
+    assert_eq!(mock.requests("artist/top/song")[0].params["id"], "4001");
+
+    let albums = nc.artist_albums(4001, 0, 30).await.unwrap();
+    assert_eq!(albums.albums[1].name, "十一月的萧邦");
+    assert!(!albums.more);
+    assert_eq!(mock.requests("artist/albums/4001")[0].params["limit"], 30);
+}
+
+#[tokio::test]
+async fn album_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    // 歌单里的歌曲带上了专辑
+    let detail = nc.play_detail(2001, &|_| {}).await.unwrap();
+    let al = detail.playlist.tracks[1].al.clone().unwrap();
+    assert_eq!(al.id, 5002);
+    // 老格式的 album 也能拿到
+    let songs = nc.recommend_songs().await.unwrap();
+    assert_eq!(songs.recommend[0].album.as_ref().unwrap().id, 5002);
+
+    let album = nc.album(al.id).await.unwrap();
+    assert_eq!(album.album.name, "十一月的萧邦");
+    assert_eq!(album.album.artist.as_ref().unwrap().id, 4001);
+    assert_eq!(album.songs.len(), 2);
+    assert_eq!(album.songs[0].al.as_ref().unwrap().id, 5002);
+
+    // 第二次走缓存
+    nc.album(al.id).await.unwrap();
+    assert_eq!(mock.requests("v1/album/5002").len(), 1);
+}
@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+
+use crate::{
+    event::ES,
+    m163::{
+        api::MusicApi,
+        mock::{Reply, setup},
+    },
+};
+
+#[tokio::test]
+async fn browse_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let list = nc.play_list(0, 10).await.unwrap();
+    assert_eq!(list.list.len(), 3);
+    assert_eq!(mock.requests("user/playlist")[0].params["uid"], 10001);
+
+    let detail = nc.play_detail(list.list[0].id, &|_| {}).await.unwrap();
+    assert_eq!(detail.playlist.tracks.len(), 2);
+    assert_eq!(mock.requests("v6/playlist/detail")[0].params["id"], 2001);
+    // 第二次走缓存
+    nc.play_detail(list.list[0].id, &|_| {}).await.unwrap();
+    assert_eq!(mock.requests("v6/playlist/detail").len(), 1);
+
+    let resource = nc.recommend_resource().await.unwrap();
+    assert_eq!(resource.recommend.len(), 2);
+    let songs = nc.recommend_songs().await.unwrap();
+    assert_eq!(songs.recommend[1].name, "稻香");
+}
+
+#[tokio::test]
+async fn play_list_paging() {
+    let (mock, nc, _rx) = setup().await;
+
+    // 不同分页各自缓存
+    assert_eq!(nc.play_list(0, 10).await.unwrap().list.len(), 3);
+    mock.set(
+        "user/playlist",
+        Reply::json(r#"{"code":200,"more":false,"playlist":[]}"#),
+    );
+    assert!(nc.play_list(0, 1000).await.unwrap().list.is_empty());
+    assert_eq!(nc.play_list(0, 10).await.unwrap().list.len(), 3);
+    assert_eq!(mock.requests("user/playlist").len(), 2);
+
+    // 按 more 翻页, 偏移取已拿到的个数
+    nc.clear_play().unwrap();
+    mock.push(
+        "user/playlist",
+        Reply::json(
+            include_str!("../../mock/user_playlist.json")
+                .replace(r#""more":false"#, r#""more":true"#)
+                .as_str(),
+        ),
+    );
+    mock.set(
+        "user/playlist",
+        Reply::json(
+            r#"{"code":200,"more":false,"playlist":[
+            {"id":2004,"name":"夜跑","subscribed":false,"coverImgUrl":"","trackCount":0,"playCount":0,"ordered":false}]}"#,
+        ),
+    );
+    let all = nc.play_list_all().await.unwrap();
+    assert_eq!(all.list.len(), 4);
+    assert_eq!(all.list[3].id, 2004);
+    let reqs = mock.requests("user/playlist");
+    assert_eq!(reqs.len(), 4);
+    assert_eq!(reqs[2].params["offset"], 0);
+    assert_eq!(reqs[3].params["offset"], 3);
+}
+
+#[tokio::test]
+async fn large_play_list() {
+    let (mock, nc, rx) = setup().await;
+    // 详情只带了第一首, 后面 1000 首要分两批补
+    let ids = (3001..=4001)
+        .map(|v| format!(r#"{{"id":{}}}"#, v))
+        .collect::<Vec<_>>()
+        .join(",");
+    mock.set(
+        "v6/playlist/detail",
+        Reply::json(&format!(
+            r#"{{"code":200,"playlist":{{"id":2009,"name":"big","tags":[],
+            "tracks":[{{"name":"晴天","id":3001,"dt":269000,"ar":[]}}],
+            "trackIds":[{}]}}}}"#,
+            ids
+        )),
+    );
+
+    let partial = Mutex::new(vec![]);
+    let detail = nc
+        .play_detail(2009, &|d| {
+            partial.lock().unwrap().push(d.playlist.tracks.len());
+        })
+        .await
+        .unwrap();
+    let reqs = mock.requests("v3/song/detail");
+    assert_eq!(reqs.len(), 2);
+    let c = reqs[0].params["c"].as_str().unwrap();
+    assert!(c.starts_with(r#"[{"id":3002},"#));
+    assert_eq!(c.matches("id").count(), 500);
+    // 按 trackIds 的顺序合并, 拿不到的跳过
+    assert_eq!(
+        detail
+            .playlist
+            .tracks
+            .iter()
+            .map(|v| v.id)
+            .collect::<Vec<_>>(),
+        vec![3001, 3002, 3003]
+    );
+    assert_eq!(detail.playlist.track_ids.len(), 3);
+
+    // 每批之前先把已有的交给调用方, 客户端自己不发 ui 事件
+    assert_eq!(partial.into_inner().unwrap(), vec![1, 3]);
+    assert!(
+        !rx.try_iter()
+            .any(|v| matches!(v, ES::DataPlayListDetail(_)))
+    );
+
+    // 完整的结果进了缓存
+    nc.play_detail(2009, &|_| {}).await.unwrap();
+    assert_eq!(mock.requests("v6/playlist/detail").len(), 1);
+    assert_eq!(mock.requests("v3/song/detail").len(), 2);
+}
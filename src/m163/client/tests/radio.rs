@@ -0,0 +1,49 @@
+use crate::m163::{api::MusicApi, mock::setup, typ::PlayItem};
+
+#[tokio::test]
+async fn fm_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let fm = nc.fm().await.unwrap();
+    assert_eq!(fm.data.len(), 3);
+    let item: PlayItem = fm.data.into_iter().next().unwrap().into();
+    assert_eq!(item.id, 3003);
+    assert_eq!(item.dt, 223000);
+    assert_eq!(item.al.unwrap().id, 5004);
+
+    nc.fm_trash(3003).await.unwrap();
+    assert_eq!(mock.requests("radio/trash/add")[0].params["songId"], 3003);
+}
+
+#[tokio::test]
+async fn simi_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let songs = nc.simi_song(3001).await.unwrap();
+    assert_eq!(songs.songs.len(), 2);
+    let item: PlayItem = songs.songs.into_iter().next().unwrap().into();
+    assert_eq!(item.id, 3002);
+    assert_eq!(item.art_r[0].id, 4001);
+    assert_eq!(
+        mock.requests("v1/discovery/simiSong")[0].params["songid"],
+        3001
+    );
+
+    let lists = nc.simi_play_list(3001).await.unwrap();
+    assert_eq!(lists.playlists.len(), 1);
+    assert_eq!(lists.playlists[0].id, 2002);
+    assert_eq!(lists.playlists[0].track_count, 30);
+}
+
+#[tokio::test]
+async fn intelligence_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let ret = nc.intelligence(2001, 3001).await.unwrap();
+    assert_eq!(ret.data.len(), 2);
+    assert_eq!(ret.data[0].id, 3004);
+    assert_eq!(ret.data[0].song_info.as_ref().unwrap().id, 3004);
+    let req = &mock.requests("playmode/intelligence/list")[0];
+    assert_eq!(req.params["playlistId"], 2001);
+    assert_eq!(req.params["songId"], 3001);
+}
@@ -0,0 +1,69 @@
+use crate::m163::{
+    api::MusicApi,
+    mock::{Reply, setup},
+    typ::SearchType,
+};
+
+#[tokio::test]
+async fn search_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let ret = nc.search("晴天", SearchType::Song, 0, 30).await.unwrap();
+    assert_eq!(ret.result.songs.len(), 2);
+    assert_eq!(ret.result.songs[0].song.name, "晴天");
+    let req = &mock.requests("cloudsearch/get/web")[0];
+    assert_eq!(req.method, "POST");
+    assert!(req.headers["content-type"].starts_with("application/x-www-form-urlencoded"));
+    assert_eq!(req.params["s"], "晴天");
+    assert_eq!(req.params["type"], "1");
+    assert_eq!(req.params["limit"], "30");
+
+    // 其它类型与翻页
+    mock.set(
+        "cloudsearch/get/web",
+        Reply::json(
+            r#"{"code":200,"result":{"albumCount":31,"albums":[{"id":4001,"name":"叶惠美","size":11,"artist":{"id":5001,"name":"周杰伦"}}]}}"#,
+        ),
+    );
+    let ret = nc
+        .search("叶惠美", SearchType::Album, 30, 30)
+        .await
+        .unwrap();
+    assert_eq!(ret.result.album_count, 31);
+    assert_eq!(ret.result.albums[0].artist.as_ref().unwrap().id, 5001);
+    assert!(ret.result.songs.is_empty());
+    let req = &mock.requests("cloudsearch/get/web")[1];
+    assert_eq!(req.params["type"], "10");
+    assert_eq!(req.params["offset"], "30");
+
+    mock.set(
+        "cloudsearch/get/web",
+        Reply::json(
+            r#"{"code":200,"result":{"songCount":1,"songs":[{"id":3001,"name":"晴天","dt":269000,"ar":[{"id":5001,"name":"周杰伦"}],"lyrics":{"txt":"故事的小黄花"}}]}}"#,
+        ),
+    );
+    let ret = nc.search("小黄花", SearchType::Lyric, 0, 30).await.unwrap();
+    assert!(ret.result.songs[0].lyrics.is_some());
+}
+
+#[tokio::test]
+async fn suggest_flow() {
+    let (mock, nc, _rx) = setup().await;
+
+    let ret = nc.search_suggest("晴").await.unwrap();
+    assert_eq!(ret.keywords, vec!["晴天", "晴天 周杰伦"]);
+    assert_eq!(ret.songs[0].id, 3001);
+    assert_eq!(ret.artists[0].id, 5001);
+    assert_eq!(mock.requests("search/suggest/web")[0].params["s"], "晴");
+
+    // 关键词补全失败不影响其它建议
+    mock.set("search/suggest/keyword", Reply::json(r#"{"code":404}"#));
+    let ret = nc.search_suggest("晴").await.unwrap();
+    assert!(ret.keywords.is_empty());
+    assert_eq!(ret.songs.len(), 1);
+
+    // 都失败时当作没有建议
+    mock.set("search/suggest/web", Reply::json(r#"{"code":404}"#));
+    let ret = nc.search_suggest("晴").await.unwrap();
+    assert!(ret.songs.is_empty() && ret.artists.is_empty() && ret.keywords.is_empty());
+}
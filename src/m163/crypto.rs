@@ -0,0 +1,189 @@
+use aes::cipher::{
+    BlockDecryptMut, BlockEncryptMut, BlockSizeUser, KeyIvInit, block_padding::Pkcs7,
+};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use num_bigint::BigUint;
+use num_traits::Num;
+use rand::Rng;
+
+use crate::m163::client::NCErr;
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
+
+pub const PRESET_KEY: &[u8; 16] = b"0CoJUm6Qyw8W8jud";
+pub const IV: &[u8; 16] = b"0102030405060708";
+pub const PUBKEY: &str = "010001";
+pub const MODULUS: &str = "00e0b509f6259df8642dbc35662901477df22677ec152b5ff68ace615bb7b725152b3ab17a876aea8a5aa76d2e417629ec4ee341f56135fccf695280104e0312ecbda92557c93870114af6c9d05c4f7f0c3685b7a46bee255932575cce10b424d813cfe4875d3e82047b97ddef52741d546b8e289dc6935b3ece0462db0a22b8e7";
+
+/// 网页端生成 secret 用的字符表
+const SECRET_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+pub fn pkcs7_padded_len(len: usize, block: usize) -> usize {
+    // 断言block_size是2的幂
+    debug_assert!(block != 0 && (block & (block - 1)) == 0);
+    (len + block) & !(block - 1)
+}
+
+pub fn rsa_no_padding(text: &[u8], pubkey: &str, modulus: &str) -> String {
+    // 反转字节
+    let mut bytes = text.to_vec();
+    bytes.reverse();
+
+    // 转成 hex，再转大整数
+    let hex_str = hex::encode(bytes);
+    let m = BigUint::from_str_radix(&hex_str, 16).unwrap();
+    let e = BigUint::from_str_radix(pubkey, 16).unwrap();
+    let n = BigUint::from_str_radix(modulus, 16).unwrap();
+
+    // 幂模运算
+    let c = m.modpow(&e, &n);
+
+    // 补零输出（256字符，或根据 modulus 字节数决定）
+    let k = n.bits().div_ceil(8) as usize;
+    format!("{:0>width$x}", c, width = k * 2)
+}
+
+fn aes_cbc_base64(data: &[u8], key: &[u8]) -> Result<String, NCErr> {
+    let len = data.len();
+    let mut buf = data.to_vec();
+    buf.resize(pkcs7_padded_len(len, aes::Aes128::block_size()), 0);
+    let out = Aes128CbcEnc::new(key.into(), IV.into())
+        .encrypt_padded_mut::<Pkcs7>(&mut buf, len)
+        .map_err(|err| NCErr::Client("aes".to_owned(), err.to_string()))?;
+    Ok(BASE64_STANDARD.encode(out))
+}
+
+fn base64_aes_cbc(data: &[u8], key: &[u8]) -> Result<Vec<u8>, NCErr> {
+    let mut buf = BASE64_STANDARD
+        .decode(data)
+        .map_err(|err| NCErr::Client("base64".to_owned(), err.to_string()))?;
+    let len = Aes128CbcDec::new(key.into(), IV.into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .map_err(|err| NCErr::Client("aes".to_owned(), err.to_string()))?
+        .len();
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// weapi 表单, 对应请求体里的 params 与 encSecKey
+#[derive(Debug, Clone)]
+pub struct WeapiForm {
+    pub params: String,
+    pub enc_sec_key: String,
+}
+
+/// weapi 加解密
+///
+/// 明文先用 [`PRESET_KEY`] 做一次 AES-CBC + base64, 再用每次随机生成的 secret 做一次,
+/// secret 经 RSA(无填充) 加密后作为 encSecKey 一起提交
+#[derive(Debug, Clone)]
+pub struct WeapiCipher {
+    pubkey: String,
+    modulus: String,
+}
+
+impl Default for WeapiCipher {
+    fn default() -> Self {
+        WeapiCipher::new(PUBKEY, MODULUS)
+    }
+}
+
+impl WeapiCipher {
+    pub fn new(pubkey: &str, modulus: &str) -> Self {
+        WeapiCipher {
+            pubkey: pubkey.to_owned(),
+            modulus: modulus.to_owned(),
+        }
+    }
+
+    /// 与网页端一致, 从字母数字里随机取 16 个字符
+    pub fn secret() -> [u8; 16] {
+        let mut rng = rand::thread_rng();
+        let mut ret = [0_u8; 16];
+        ret.iter_mut()
+            .for_each(|v| *v = SECRET_CHARS[rng.gen_range(0..SECRET_CHARS.len())]);
+        ret
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> Result<WeapiForm, NCErr> {
+        self.encrypt_with(data, &Self::secret())
+    }
+
+    pub fn encrypt_with(&self, data: &[u8], secret: &[u8; 16]) -> Result<WeapiForm, NCErr> {
+        let first = aes_cbc_base64(data, PRESET_KEY)?;
+        Ok(WeapiForm {
+            params: aes_cbc_base64(first.as_bytes(), secret)?,
+            enc_sec_key: rsa_no_padding(secret, &self.pubkey, &self.modulus),
+        })
+    }
+
+    /// 已知 secret 时还原 params 明文
+    pub fn decrypt(params: &str, secret: &[u8; 16]) -> Result<Vec<u8>, NCErr> {
+        let first = base64_aes_cbc(params.as_bytes(), secret)?;
+        base64_aes_cbc(&first, PRESET_KEY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padded_len() {
+        assert_eq!(pkcs7_padded_len(0, 16), 16);
+        assert_eq!(pkcs7_padded_len(1, 16), 16);
+        assert_eq!(pkcs7_padded_len(15, 16), 16);
+        assert_eq!(pkcs7_padded_len(16, 16), 32);
+        assert_eq!(pkcs7_padded_len(17, 16), 32);
+        assert_eq!(pkcs7_padded_len(33, 8), 40);
+    }
+
+    #[test]
+    fn rsa_small() {
+        // 2^3 mod 33, 输出按 modulus 字节数补零
+        assert_eq!(rsa_no_padding(&[0x02], "03", "21"), "08");
+        // 字节会先反转: [0x01, 0x00] -> 0x0001
+        assert_eq!(rsa_no_padding(&[0x01, 0x00], "03", "ffff"), "0001");
+    }
+
+    #[test]
+    fn rsa_known_answer() {
+        assert_eq!(
+            rsa_no_padding(&[0x42; 16], PUBKEY, MODULUS),
+            "bd1b5330f7ad96acf381282457b6e47b542a9cb291adbcff7e954eb5867d9cfb398928ecdbede6e6d15acbe22396e057f2f042c26c9127d2750bc31af7bbcffb7c7671eaefd61320a2dd489811eb462106c18d790dd79dc7c65050c1e33bd8a72f7fc30b7b22261e78ec7aa6f07352b70612fa070222d76a2fcd2877eb52725c"
+        );
+    }
+
+    #[test]
+    fn encrypt_known_answer() {
+        let form = WeapiCipher::default()
+            .encrypt_with(br#"{"id":"1","tv":-1}"#, b"abcdefghijklmnop")
+            .unwrap();
+        assert_eq!(
+            form.params,
+            "B8ZaaaFLduLEMz3ojlZmq7ZjlXOy1RJwsPbRT2unH6Ach5hn2WaWOHvRdo+bpxcS"
+        );
+        assert_eq!(
+            form.enc_sec_key,
+            "d15a1683c992095d0c234c19966605c5c5964911268bbeda8cb8d08d834913e59d53b32358903a121b5fca784c1f5ae44951fd02524df58ecc98e52cc7cf8689b42c2e93ddf05b0592512d87f5960467e2f086c018849d76014d323500e30f13ef4cafbb0cf5a66731a3f1776c75ca35d0062dac70a3e33245afabcf47938487"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let secret = WeapiCipher::secret();
+        assert!(secret.iter().all(|v| SECRET_CHARS.contains(v)));
+        let data = r#"{"s":"晴天","offset":"0"}"#.as_bytes();
+        let form = WeapiCipher::default().encrypt_with(data, &secret).unwrap();
+        assert_eq!(WeapiCipher::decrypt(&form.params, &secret).unwrap(), data);
+    }
+
+    #[test]
+    fn secret_per_request() {
+        let c = WeapiCipher::default();
+        let a = c.encrypt(b"{}").unwrap();
+        let b = c.encrypt(b"{}").unwrap();
+        assert_ne!(a.enc_sec_key, b.enc_sec_key);
+    }
+}
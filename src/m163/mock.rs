@@ -0,0 +1,433 @@
+//! 本地模拟的网易云接口, 只在测试里使用
+//!
+//! 服务端会解开 weapi 的请求参数并记录下来, 按接口路径返回 `mock/` 下的 json,
+//! `/audio/*` 提供下载用的音频文件. 测试用 [`MockServer::nc`] 拿到指向它的 `Nc`.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex, mpsc},
+};
+
+use num_bigint::BigUint;
+use num_traits::Num;
+use reqwest::Url;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use crate::{
+    config::Config,
+    event::ES,
+    m163::{client::Nc, crypto::WeapiCipher},
+};
+
+/// 测试用的 RSA 密钥, 真实的私钥拿不到, 只能让客户端换成这一对
+pub const TEST_PUBKEY: &str = "010001";
+pub const TEST_MODULUS: &str = "eed7454eb76240741aed8c00e0b027ba1bd84a87d9cbc625ae9e76c40183afb9c422b6d64f44eb703e025a8ba3efab2883027e6e3c3e0bc71604637f7de0c34880f50158d74513047f4084c3b344247a8d9410b4ab62a91a62807d55ee0e1c9d8b3a409ac3c8c11f7bbe83d27477c90749c2aa95d2363f5b9a8f4ea8352d1e13";
+const TEST_PRIVKEY: &str = "8fa0cf22062fc498cdb4ba73b0aade491203a0d64f483691649010d5d01f53e67fa76c92363b38e657b3eca57ae15ee94ed78a165a8de96140c144e05f5f71af17ab2821ac82bca0351333404b9bf164e2c7762f704a451e12ddecae3bdf8e94cf0d39ec26ab350049be15baff4d0e1130828c46f1d39b2a5aaea3af3024ffe1";
+
+pub const AUDIO: &[u8] = include_bytes!("mock/silence.mp3");
+
+const FIXTURES: &[(&str, &str)] = &[
+    ("login/qrcode/unikey", include_str!("mock/qr_unikey.json")),
+    (
+        "login/qrcode/client/login",
+        include_str!("mock/qr_login.json"),
+    ),
+    ("logout", include_str!("mock/ok.json")),
+    ("w/nuser/account/get", include_str!("mock/profile.json")),
+    ("user/playlist", include_str!("mock/user_playlist.json")),
+    (
+        "v6/playlist/detail",
+        include_str!("mock/playlist_detail.json"),
+    ),
+    ("playlist/create", include_str!("mock/ok.json")),
+    ("playlist/delete", include_str!("mock/ok.json")),
+    ("playlist/subscribe", include_str!("mock/ok.json")),
+    ("playlist/unsubscribe", include_str!("mock/ok.json")),
+    ("playlist/manipulate/tracks", include_str!("mock/ok.json")),
+    (
+        "song/enhance/player/url/v1",
+        include_str!("mock/song_url.json"),
+    ),
+    ("song/lyric", include_str!("mock/lyric.json")),
+    ("cloudsearch/get/web", include_str!("mock/search.json")),
+    ("search/suggest/web", include_str!("mock/suggest_web.json")),
+    (
+        "search/suggest/keyword",
+        include_str!("mock/suggest_keyword.json"),
+    ),
+    ("v1/radio/get", include_str!("mock/fm.json")),
+    ("radio/trash/add", include_str!("mock/ok.json")),
+    (
+        "playmode/intelligence/list",
+        include_str!("mock/intelligence.json"),
+    ),
+    (
+        "v1/resource/comments/R_SO_4_3001",
+        include_str!("mock/comments.json"),
+    ),
+    (
+        "v1/resource/hotcomments/A_PL_0_2001",
+        include_str!("mock/hot_comments.json"),
+    ),
+    ("toplist", include_str!("mock/toplist.json")),
+    (
+        "v1/discovery/new/songs",
+        include_str!("mock/new_songs.json"),
+    ),
+    ("album/new", include_str!("mock/new_albums.json")),
+    ("v1/discovery/simiSong", include_str!("mock/simi_song.json")),
+    (
+        "discovery/simiPlaylist",
+        include_str!("mock/simi_playlist.json"),
+    ),
+    ("radio/like", include_str!("mock/ok.json")),
+    ("song/like/get", include_str!("mock/like_list.json")),
+    ("v3/song/detail", include_str!("mock/song_detail.json")),
+    ("v1/album/5002", include_str!("mock/album.json")),
+    ("v1/artist/4001", include_str!("mock/artist.json")),
+    ("artist/top/song", include_str!("mock/artist_songs.json")),
+    (
+        "artist/albums/4001",
+        include_str!("mock/artist_albums.json"),
+    ),
+    (
+        "discovery/recommend/resource",
+        include_str!("mock/recommend_resource.json"),
+    ),
+    (
+        "v2/discovery/recommend/songs",
+        include_str!("mock/recommend_songs.json"),
+    ),
+];
+
+/// 服务端收到的一次请求, params 为解密后的明文
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub params: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    pub fn json(body: &str) -> Reply {
+        Reply {
+            status: 200,
+            headers: vec![("Content-Type".to_owned(), "application/json".to_owned())],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    routes: HashMap<String, Reply>,
+    // 排队的一次性返回, 用完再走 routes
+    queued: HashMap<String, VecDeque<Reply>>,
+    requests: Vec<Request>,
+}
+
+pub struct MockServer {
+    base: String,
+    home: PathBuf,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_dir_all(&self.home);
+    }
+}
+
+impl MockServer {
+    pub async fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        {
+            let mut s = state.lock().unwrap();
+            for (path, body) in FIXTURES {
+                s.routes.insert(
+                    (*path).to_owned(),
+                    Reply::json(&body.replace("{base}", &base)),
+                );
+            }
+        }
+        let task = tokio::spawn({
+            let state = state.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(serve(stream, state.clone()));
+                }
+            }
+        });
+        MockServer {
+            base,
+            home: std::env::temp_dir().join(format!("m-lite-mock-{}", fastrand::u64(..))),
+            state,
+            task,
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// 覆盖某个接口的返回
+    pub fn set(&self, path: &str, reply: Reply) {
+        self.state
+            .lock()
+            .unwrap()
+            .routes
+            .insert(path.to_owned(), reply);
+    }
+
+    /// 下一次请求这个接口时的返回, 只用一次
+    pub fn push(&self, path: &str, reply: Reply) {
+        self.state
+            .lock()
+            .unwrap()
+            .queued
+            .entry(path.to_owned())
+            .or_default()
+            .push_back(reply);
+    }
+
+    /// 某个接口收到过的请求
+    pub fn requests(&self, path: &str) -> Vec<Request> {
+        self.state
+            .lock()
+            .unwrap()
+            .requests
+            .iter()
+            .filter(|v| v.path.eq(path))
+            .cloned()
+            .collect()
+    }
+
+    pub fn config(&self) -> Config {
+        let mut c = Config {
+            home_dir: self.home.to_string_lossy().into_owned(),
+            api_base: self.base.to_owned(),
+            ..Default::default()
+        };
+        c.init();
+        c
+    }
+
+    pub fn nc(&self) -> (Nc, mpsc::Receiver<ES>) {
+        self.nc_with(self.config())
+    }
+
+    /// 用改过的配置连到这个服务
+    pub fn nc_with(&self, c: Config) -> (Nc, mpsc::Receiver<ES>) {
+        let (tx, rx) = mpsc::channel();
+        let nc = Nc::new(tx, c)
+            .unwrap()
+            .with_weapi(WeapiCipher::new(TEST_PUBKEY, TEST_MODULUS));
+        (nc, rx)
+    }
+}
+
+/// 起一个服务并连上, 接口测试的公共开头
+pub async fn setup() -> (MockServer, Nc, mpsc::Receiver<ES>) {
+    let mock = MockServer::start().await;
+    let (nc, rx) = mock.nc();
+    (mock, nc, rx)
+}
+
+/// 用测试私钥还原 encSecKey 里的 secret
+fn recover_secret(enc_sec_key: &str) -> Option<[u8; 16]> {
+    let c = BigUint::from_str_radix(enc_sec_key, 16).ok()?;
+    let d = BigUint::from_str_radix(TEST_PRIVKEY, 16).ok()?;
+    let n = BigUint::from_str_radix(TEST_MODULUS, 16).ok()?;
+    let mut bytes = c.modpow(&d, &n).to_bytes_be();
+    if bytes.len() > 16 {
+        return None;
+    }
+    // 加密前反转过, 高位的 0 在大整数里会丢掉
+    bytes.splice(0..0, std::iter::repeat_n(0, 16 - bytes.len()));
+    bytes.reverse();
+    bytes.try_into().ok()
+}
+
+fn form(body: &[u8]) -> HashMap<String, String> {
+    match Url::parse(&format!("http://form/?{}", String::from_utf8_lossy(body))) {
+        Ok(u) => u.query_pairs().into_owned().collect(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// 按传输方式解开请求, 返回接口路径与参数
+fn decode(target: &str, body: &[u8]) -> Option<(String, serde_json::Value)> {
+    let form = form(body);
+    if let Some(path) = target.strip_prefix("/weapi/") {
+        let secret = recover_secret(form.get("encSecKey")?)?;
+        let plain = WeapiCipher::decrypt(form.get("params")?, &secret).ok()?;
+        return Some((path.to_owned(), serde_json::from_slice(&plain).ok()?));
+    }
+    None
+}
+
+/// 测试音频, 支持 `Range: bytes=N-` 与 `If-Range`, 起点超出文件时给 416
+fn audio(headers: &HashMap<String, String>) -> Reply {
+    const ETAG: &str = "\"silence\"";
+    let start = headers
+        .get("range")
+        .and_then(|v| v.strip_prefix("bytes="))
+        .and_then(|v| v.trim_end_matches('-').parse::<usize>().ok())
+        .filter(|_| headers.get("if-range").is_none_or(|v| v == ETAG));
+    let mut headers = vec![
+        ("Content-Type".to_owned(), "audio/mpeg".to_owned()),
+        ("ETag".to_owned(), ETAG.to_owned()),
+    ];
+    match start {
+        Some(start) if start >= AUDIO.len() => {
+            headers.push((
+                "Content-Range".to_owned(),
+                format!("bytes */{}", AUDIO.len()),
+            ));
+            Reply {
+                status: 416,
+                headers,
+                body: vec![],
+            }
+        }
+        Some(start) => {
+            headers.push((
+                "Content-Range".to_owned(),
+                format!("bytes {}-{}/{}", start, AUDIO.len() - 1, AUDIO.len()),
+            ));
+            Reply {
+                status: 206,
+                headers,
+                body: AUDIO[start..].to_vec(),
+            }
+        }
+        None => Reply {
+            status: 200,
+            headers,
+            body: AUDIO.to_vec(),
+        },
+    }
+}
+
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<State>>) -> io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0_u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(i) = buf.windows(4).position(|v| v.eq(b"\r\n\r\n")) {
+            break i + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut first = lines.next().unwrap_or_default().split(' ');
+    let method = first.next().unwrap_or_default().to_owned();
+    let target = first.next().unwrap_or_default();
+    let target = target.split('?').next().unwrap_or_default().to_owned();
+    let headers = lines
+        .filter_map(|v| v.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_owned()))
+        .collect::<HashMap<_, _>>();
+    let len = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < head_end + len {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = &buf[head_end..buf.len().min(head_end + len)];
+
+    let reply = if let Some(name) = target.strip_prefix("/audio/") {
+        let reply = match name {
+            "silence.mp3" => audio(&headers),
+            _ => state
+                .lock()
+                .unwrap()
+                .routes
+                .get(&target)
+                .cloned()
+                .unwrap_or_else(|| Reply::json(r#"{"code":404}"#)),
+        };
+        state.lock().unwrap().requests.push(Request {
+            method,
+            path: target.to_owned(),
+            headers,
+            params: serde_json::Value::Null,
+        });
+        reply
+    } else {
+        match decode(&target, body) {
+            Some((path, params)) => {
+                let mut s = state.lock().unwrap();
+                let queued = s.queued.get_mut(&path).and_then(|v| v.pop_front());
+                let reply = queued
+                    .or_else(|| s.routes.get(&path).cloned())
+                    .unwrap_or_else(|| Reply::json(r#"{"code":404,"msg":"mock not found"}"#));
+                s.requests.push(Request {
+                    method,
+                    path,
+                    headers,
+                    params,
+                });
+                reply
+            }
+            None => Reply {
+                status: 400,
+                ..Reply::json(r#"{"code":400,"msg":"bad params"}"#)
+            },
+        }
+    };
+
+    let mut out = format!(
+        "HTTP/1.1 {} MOCK\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reply.body.len()
+    );
+    for (k, v) in &reply.headers {
+        out.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    out.push_str("\r\n");
+    stream.write_all(out.as_bytes()).await?;
+    stream.write_all(&reply.body).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_round_trip() {
+        let secret = WeapiCipher::secret();
+        let form = WeapiCipher::new(TEST_PUBKEY, TEST_MODULUS)
+            .encrypt_with(b"{}", &secret)
+            .unwrap();
+        assert_eq!(recover_secret(&form.enc_sec_key), Some(secret));
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+    sync::{Arc, Condvar, Mutex},
+};
+
+/// 缓存文件可能的扩展名
+pub const EXTS: &[&str] = &["mp3", "flac", "m4a"];
+
+/// 接口返回的 type/encodeType 对应的扩展名
+pub fn ext_of(kind: &str) -> &'static str {
+    match kind.to_lowercase().as_str() {
+        "flac" => "flac",
+        "m4a" | "mp4" | "aac" => "m4a",
+        _ => "mp3",
+    }
+}
+
+/// 按文件头判断容器, 带 ID3 的可能是 mp3 也可能是 flac, 交给接口的 type 判断
+pub fn sniff(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"fLaC") {
+        Some("flac")
+    } else if head.get(4..8) == Some(b"ftyp") {
+        Some("m4a")
+    } else if head.len() >= 2 && head[0] == 0xFF && head[1] & 0xE0 == 0xE0 {
+        Some("mp3")
+    } else {
+        None
+    }
+}
+
+/// 边下边播: 下载端写 `.part` 并更新进度, 播放端通过 [`GrowingFile`] 按进度读取
+#[derive(Default)]
+pub struct Progress {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    got: u64,
+    total: Option<u64>,
+    // Some(true) 下载完成, Some(false) 下载失败
+    done: Option<bool>,
+    // 播放端切歌后不再等待数据
+    closed: bool,
+    part: PathBuf,
+    path: PathBuf,
+}
+
+impl Progress {
+    pub fn new() -> Arc<Progress> {
+        Arc::new(Progress::default())
+    }
+
+    /// 下载端开始写入, part 为临时文件, path 为完成后的缓存文件
+    pub fn start(&self, part: PathBuf, path: PathBuf) {
+        let mut s = self.state.lock().unwrap();
+        s.part = part;
+        s.path = path;
+        s.got = 0;
+        s.done = None;
+        self.cond.notify_all();
+    }
+
+    /// 识别出真实格式后修正缓存文件名
+    pub fn set_path(&self, path: PathBuf) {
+        self.state.lock().unwrap().path = path;
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.state.lock().unwrap().path.clone()
+    }
+
+    pub fn update(&self, got: u64, total: Option<u64>) {
+        let mut s = self.state.lock().unwrap();
+        s.got = got;
+        s.total = total;
+        self.cond.notify_all();
+    }
+
+    pub fn finish(&self, ok: bool) {
+        self.state.lock().unwrap().done = Some(ok);
+        self.cond.notify_all();
+    }
+
+    pub fn finished(&self) -> bool {
+        self.state.lock().unwrap().done.is_some()
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        self.state.lock().unwrap().total
+    }
+
+    /// 等到至少有 n 字节或者下载结束, 下载失败时返回 false
+    pub fn wait(&self, n: u64) -> bool {
+        let mut s = self.state.lock().unwrap();
+        loop {
+            match s.done {
+                Some(ok) => return ok,
+                None if s.got >= n => return true,
+                None => s = self.cond.wait(s).unwrap(),
+            }
+        }
+    }
+
+    /// 让正在读的 [`GrowingFile`] 直接读到结尾, 切歌时用
+    pub fn close_readers(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.cond.notify_all();
+    }
+
+    /// 下载中打开 `.part`, 已经下完(改过名)则打开缓存文件
+    pub fn open(self: &Arc<Self>) -> io::Result<GrowingFile> {
+        let (part, path) = {
+            let mut s = self.state.lock().unwrap();
+            s.closed = false;
+            (s.part.clone(), s.path.clone())
+        };
+        let file = match File::open(&part) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => File::open(&path)?,
+            Err(e) => return Err(e),
+        };
+        Ok(GrowingFile {
+            file,
+            pos: 0,
+            progress: self.clone(),
+        })
+    }
+
+    // pos 之后还有多少字节可读, 没有数据时阻塞等待下载
+    fn available(&self, pos: u64) -> io::Result<u64> {
+        let mut s = self.state.lock().unwrap();
+        loop {
+            if s.closed {
+                return Ok(0);
+            }
+            if s.got > pos {
+                return Ok(s.got - pos);
+            }
+            match s.done {
+                Some(true) => return Ok(0),
+                Some(false) => return Err(io::Error::other("download failed")),
+                None => s = self.cond.wait(s).unwrap(),
+            }
+        }
+    }
+}
+
+/// 一边下载一边增长的文件, 读到未下载的部分时等待, 不允许跳到还没下载的位置
+pub struct GrowingFile {
+    file: File,
+    pos: u64,
+    progress: Arc<Progress>,
+}
+
+impl Read for GrowingFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let avail = self.progress.available(self.pos)?;
+        if avail == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        let n = buf.len().min(avail as usize);
+        let n = self.file.read(&mut buf[..n])?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for GrowingFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (got, total, done) = {
+            let s = self.progress.state.lock().unwrap();
+            (s.got, s.total, s.done)
+        };
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+            SeekFrom::End(d) => total
+                .or(done.map(|_| got))
+                .and_then(|v| v.checked_add_signed(d)),
+        }
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "bad seek"))?;
+        if target > got && done.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "not downloaded yet",
+            ));
+        }
+        self.pos = self.file.seek(SeekFrom::Start(target))?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, thread, time::Duration};
+
+    use super::*;
+
+    fn temp(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("m-lite-stream-{}-{}", name, fastrand::u64(..)))
+    }
+
+    #[test]
+    fn sniff_container() {
+        assert_eq!(sniff(b"fLaC\0\0\0\x22"), Some("flac"));
+        assert_eq!(sniff(b"\0\0\0\x20ftypM4A "), Some("m4a"));
+        assert_eq!(sniff(&[0xFF, 0xFB, 0x90, 0x64]), Some("mp3"));
+        assert_eq!(sniff(b"ID3\x04"), None);
+        assert_eq!(ext_of("FLAC"), "flac");
+        assert_eq!(ext_of("aac"), "m4a");
+        assert_eq!(ext_of(""), "mp3");
+    }
+
+    #[test]
+    fn read_waits_for_data() {
+        let part = temp("part");
+        let path = temp("done");
+        let progress = Progress::new();
+        progress.start(part.clone(), path.clone());
+        let mut w = File::create(&part).unwrap();
+        w.write_all(b"abcd").unwrap();
+        progress.update(4, Some(8));
+
+        let mut r = progress.open().unwrap();
+        let mut buf = [0_u8; 8];
+        assert_eq!(r.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"abcd");
+        // 还没下载到的位置不能跳
+        assert_eq!(
+            r.seek(SeekFrom::Start(6)).unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        let writer = thread::spawn({
+            let progress = progress.clone();
+            let (part, path) = (part.clone(), path.clone());
+            move || {
+                thread::sleep(Duration::from_millis(50));
+                w.write_all(b"efgh").unwrap();
+                progress.update(8, Some(8));
+                std::fs::rename(&part, &path).unwrap();
+                progress.finish(true);
+            }
+        });
+        let mut rest = vec![];
+        r.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"efgh");
+        writer.join().unwrap();
+        assert_eq!(r.seek(SeekFrom::End(-2)).unwrap(), 6);
+
+        // 下载完以后再打开读的是缓存文件
+        let mut all = vec![];
+        progress.open().unwrap().read_to_end(&mut all).unwrap();
+        assert_eq!(all, b"abcdefgh");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn failed_or_closed() {
+        let part = temp("part");
+        let progress = Progress::new();
+        progress.start(part.clone(), temp("done"));
+        File::create(&part).unwrap();
+        let mut r = progress.open().unwrap();
+
+        progress.close_readers();
+        assert_eq!(r.read(&mut [0_u8; 4]).unwrap(), 0);
+
+        let mut r = progress.open().unwrap();
+        progress.finish(false);
+        assert!(!progress.wait(1));
+        assert!(r.read(&mut [0_u8; 4]).is_err());
+        let _ = std::fs::remove_file(&part);
+    }
+}
@@ -7,14 +7,21 @@ pub struct Any {
     pub code: i32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Lyric {
     pub code: i32,
     pub lrc: LyricInner,
+    /// 翻译, 没有时为空或缺省
+    #[serde(default)]
+    pub tlyric: Option<LyricInner>,
+    /// 音译(罗马音)
+    #[serde(default)]
+    pub romalrc: Option<LyricInner>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LyricInner {
+    #[serde(default)]
     pub lyric: String,
 }
 
@@ -27,15 +34,158 @@ pub struct QRLogin {
 #[derive(Debug, Deserialize)]
 pub struct SearchResult {
     pub code: i32,
+    #[serde(default)]
     pub result: SearchResultInner,
 }
 
-#[derive(Debug, Deserialize)]
+/// cloudsearch 的 type 参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchType {
+    Song = 1,
+    Album = 10,
+    Artist = 100,
+    PlayList = 1000,
+    User = 1002,
+    Lyric = 1006,
+}
+
+/// 评论所属资源, 决定线程 id 的前缀
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommentType {
+    Song,
+    PlayList,
+}
+
+impl CommentType {
+    pub fn thread(&self, id: usize) -> String {
+        match self {
+            CommentType::Song => format!("R_SO_4_{}", id),
+            CommentType::PlayList => format!("A_PL_0_{}", id),
+        }
+    }
+}
+
+/// 不同 type 只会填充对应的列表与个数
+#[derive(Debug, Deserialize, Default)]
 pub struct SearchResultInner {
-    #[serde(rename = "songCount")]
+    #[serde(rename = "songCount", default)]
     pub song_count: u32,
     #[serde(default)]
-    pub songs: Vec<PlayItem>,
+    pub songs: Vec<SearchSong>,
+    #[serde(rename = "albumCount", default)]
+    pub album_count: u32,
+    #[serde(default)]
+    pub albums: Vec<SearchAlbum>,
+    #[serde(rename = "artistCount", default)]
+    pub artist_count: u32,
+    #[serde(default)]
+    pub artists: Vec<SearchArtist>,
+    #[serde(rename = "playlistCount", default)]
+    pub playlist_count: u32,
+    #[serde(default)]
+    pub playlists: Vec<SearchPlayList>,
+    #[serde(rename = "userprofileCount", default)]
+    pub user_count: u32,
+    #[serde(rename = "userprofiles", default)]
+    pub users: Vec<SearchUser>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestWeb {
+    #[serde(default)]
+    pub result: SuggestWebInner,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct SuggestWebInner {
+    #[serde(default)]
+    pub songs: Vec<SuggestSong>,
+    #[serde(default)]
+    pub artists: Vec<Arter>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SuggestSong {
+    pub id: usize,
+    pub name: String,
+    #[serde(default)]
+    pub artists: Vec<Arter>,
+    #[serde(default)]
+    pub duration: u64,
+    #[serde(default)]
+    pub album: Option<PlayAlbum>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestKeyword {
+    #[serde(default)]
+    pub result: SuggestKeywordInner,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct SuggestKeywordInner {
+    #[serde(rename = "allMatch", default)]
+    pub all_match: Vec<SuggestKeywordItem>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SuggestKeywordItem {
+    pub keyword: String,
+}
+
+/// 搜索建议, 由关键词补全与网页端建议两个接口合并
+#[derive(Debug, Clone, Default)]
+pub struct SearchSuggest {
+    pub keywords: Vec<String>,
+    pub artists: Vec<Arter>,
+    pub songs: Vec<SuggestSong>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SearchSong {
+    #[serde(flatten)]
+    pub song: PlayItem,
+    /// 歌词搜索时命中的片段, 格式不固定
+    #[serde(default)]
+    pub lyrics: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SearchAlbum {
+    pub id: usize,
+    pub name: String,
+    #[serde(default)]
+    pub artist: Option<Arter>,
+    /// 歌曲个数
+    #[serde(default)]
+    pub size: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SearchArtist {
+    pub id: usize,
+    pub name: String,
+    #[serde(rename = "albumSize", default)]
+    pub album_size: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SearchPlayList {
+    pub id: usize,
+    pub name: String,
+    #[serde(rename = "trackCount", default)]
+    pub track_count: usize,
+    #[serde(default)]
+    pub creator: PlayCreator,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SearchUser {
+    #[serde(rename = "userId")]
+    pub user_id: usize,
+    pub nickname: String,
+    #[serde(default)]
+    pub signature: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -189,7 +339,7 @@ pub struct PlayDetail {
     pub playlist: PlayDetailInner,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct PlayDetailInner {
     #[serde(default)]
     pub id: usize,
@@ -212,8 +362,23 @@ pub struct PlayDetailInner {
     pub play_count: u64,
     #[serde(default)]
     pub creator: PlayCreator,
-    /// 收录歌曲
+    /// 收录歌曲, 大歌单只会带前一部分
     pub tracks: Vec<PlayItem>,
+    /// 全部歌曲 id
+    #[serde(rename = "trackIds", default)]
+    pub track_ids: Vec<TrackId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrackId {
+    pub id: usize,
+}
+
+/// v3/song/detail, 与歌单里的歌曲同一个格式
+#[derive(Debug, Deserialize)]
+pub struct SongDetail {
+    #[serde(default)]
+    pub songs: Vec<PlayItem>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -235,6 +400,16 @@ pub struct PlayItem {
     // /// 作者列表
     #[serde(rename = "ar")]
     pub art_r: Vec<Arter>,
+    /// 所属专辑, 老格式接口的 album 由 [`SongItem`] 转换过来
+    #[serde(default)]
+    pub al: Option<PlayAlbum>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayAlbum {
+    pub id: usize,
+    #[serde(default)]
+    pub name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -249,6 +424,101 @@ pub struct Arter {
     pub name: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ArtistDetail {
+    pub artist: ArtistInfo,
+    /// 附带的热门歌曲, 完整的 50 首走 [`ArtistSongs`]
+    #[serde(rename = "hotSongs", default)]
+    pub hot_songs: Vec<PlayItem>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ArtistInfo {
+    pub id: usize,
+    pub name: String,
+    /// 简介
+    #[serde(rename = "briefDesc", default)]
+    pub brief_desc: Option<String>,
+    #[serde(rename = "albumSize", default)]
+    pub album_size: u32,
+    #[serde(rename = "musicSize", default)]
+    pub music_size: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArtistSongs {
+    #[serde(default)]
+    pub songs: Vec<PlayItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArtistAlbums {
+    #[serde(rename = "hotAlbums", default)]
+    pub albums: Vec<ArtistAlbum>,
+    #[serde(default)]
+    pub more: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArtistAlbum {
+    pub id: usize,
+    pub name: String,
+    /// 新碟上架里用来显示歌手
+    #[serde(default)]
+    pub artist: Option<Arter>,
+    /// 歌曲个数
+    #[serde(default)]
+    pub size: u32,
+    /// 发行时间, 毫秒
+    #[serde(rename = "publishTime", default)]
+    pub publish_time: i64,
+}
+
+/// song/like/get, 喜欢的歌曲 id
+#[derive(Debug, Deserialize)]
+pub struct LikeList {
+    #[serde(default)]
+    pub ids: Vec<usize>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AlbumDetail {
+    pub album: AlbumInfo,
+    #[serde(default)]
+    pub songs: Vec<PlayItem>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AlbumInfo {
+    pub id: usize,
+    pub name: String,
+    #[serde(default)]
+    pub artist: Option<Arter>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 发行时间, 毫秒
+    #[serde(rename = "publishTime", default)]
+    pub publish_time: i64,
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub size: u32,
+}
+
+impl From<AlbumDetail> for PlayDetail {
+    fn from(v: AlbumDetail) -> Self {
+        PlayDetail {
+            // 专辑不是歌单, id 留 0 不让当歌单收藏或修改
+            playlist: PlayDetailInner {
+                name: v.album.name,
+                description: v.album.description,
+                tracks: v.songs,
+                ..Default::default()
+            },
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SongUrl {
     pub data: Vec<SongUrlItem>,
@@ -258,6 +528,14 @@ pub struct SongUrl {
 pub struct SongUrlItem {
     pub url: String,
     pub time: usize,
+    #[serde(default)]
+    pub br: usize,
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default, rename = "encodeType")]
+    pub encode_type: Option<String>,
+    #[serde(default)]
+    pub level: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -272,6 +550,125 @@ pub struct SongItem {
     pub name: String,
     pub artists: Vec<Arter>,
     pub duration: u64,
+    #[serde(default)]
+    pub album: Option<PlayAlbum>,
+}
+
+impl From<SongItem> for PlayItem {
+    fn from(v: SongItem) -> Self {
+        PlayItem {
+            name: v.name,
+            id: v.id,
+            dt: v.duration,
+            art_r: v.artists,
+            al: v.album,
+        }
+    }
+}
+
+/// v1/radio/get, 私人 FM 的一批歌曲
+#[derive(Debug, Deserialize)]
+pub struct PersonalFm {
+    #[serde(default)]
+    pub data: Vec<SongItem>,
+}
+
+/// v1/resource/comments 与 hotcomments, 热门评论只在第一页带
+#[derive(Debug, Deserialize)]
+pub struct Comments {
+    #[serde(default)]
+    pub total: usize,
+    #[serde(alias = "hasMore", default)]
+    pub more: bool,
+    #[serde(rename = "hotComments", default)]
+    pub hot_comments: Vec<Comment>,
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Comment {
+    #[serde(rename = "commentId")]
+    pub comment_id: usize,
+    pub content: String,
+    /// 毫秒, 也是最新评论翻页的游标
+    #[serde(default)]
+    pub time: i64,
+    #[serde(rename = "likedCount", default)]
+    pub liked_count: usize,
+    pub user: CommentUser,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CommentUser {
+    #[serde(rename = "userId", default)]
+    pub user_id: usize,
+    #[serde(default)]
+    pub nickname: String,
+}
+
+/// toplist, 排行榜目录
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TopList {
+    #[serde(default)]
+    pub list: Vec<TopListItem>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TopListItem {
+    /// 榜单就是歌单, 用歌单详情取歌曲
+    pub id: usize,
+    pub name: String,
+    #[serde(rename = "coverImgUrl", default)]
+    pub cover_img_url: String,
+    #[serde(rename = "trackCount", default)]
+    pub track_count: usize,
+    #[serde(rename = "playCount", default)]
+    pub play_count: usize,
+    #[serde(rename = "updateFrequency", default)]
+    pub update_frequency: String,
+}
+
+/// v1/discovery/new/songs, 新歌速递
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NewSongs {
+    #[serde(default)]
+    pub data: Vec<SongItem>,
+}
+
+/// album/new, 新碟上架
+#[derive(Debug, Deserialize, Serialize)]
+pub struct NewAlbums {
+    #[serde(default)]
+    pub albums: Vec<ArtistAlbum>,
+}
+
+/// v1/discovery/simiSong, 相似歌曲
+#[derive(Debug, Deserialize)]
+pub struct SimiSong {
+    #[serde(default)]
+    pub songs: Vec<SongItem>,
+}
+
+/// discovery/simiPlaylist, 包含相似歌曲的歌单
+#[derive(Debug, Deserialize)]
+pub struct SimiPlayList {
+    #[serde(default)]
+    pub playlists: Vec<SearchPlayList>,
+}
+
+/// playmode/intelligence/list, 心动模式的推荐
+#[derive(Debug, Deserialize)]
+pub struct Intelligence {
+    #[serde(default)]
+    pub data: Vec<IntelligenceItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IntelligenceItem {
+    pub id: usize,
+    #[serde(rename = "songInfo", default)]
+    pub song_info: Option<PlayItem>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -6,7 +6,7 @@ use std::{
 use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind};
 use tokio_util::sync::CancellationToken;
 
-use crate::{event::ES, play::PlayCtx, ui::app::App};
+use crate::{event::ES, m163::api::MusicApi, play::PlayCtx, ui::app::App};
 
 mod config;
 mod event;
@@ -20,7 +20,8 @@ fn main() {
     let mut c = config::load();
     let tc = CancellationToken::new();
     let (event_tx, event_rx) = mpsc::channel::<ES>();
-    let nn = Arc::new(m163::client::Nc::new(event_tx.clone(), c.clone()).unwrap());
+    let nn: Arc<dyn MusicApi> =
+        Arc::new(m163::client::Nc::new(event_tx.clone(), c.clone()).unwrap());
     let mut terminal = ratatui::init();
 
     let stream_handle =
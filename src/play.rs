@@ -1,11 +1,12 @@
 use std::{
+    collections::HashMap,
     fs::OpenOptions,
     rc::Rc,
     sync::{Arc, mpsc::Sender},
     time::Duration,
 };
 
-use rodio::Sink;
+use rodio::{Decoder, Sink};
 use tokio::{
     select,
     sync::mpsc::{self, UnboundedSender},
@@ -15,7 +16,11 @@ use tokio_util::sync::CancellationToken;
 use crate::{
     config::Config,
     event::{AppState, ES, LoginState, Play, PlayState},
-    m163::client::Nc,
+    m163::{
+        api::MusicApi,
+        stream::{GrowingFile, Progress},
+        typ::Lyric,
+    },
     ui::{
         app::Wrap,
         widgets::tip::{Msg, SimpleMsg},
@@ -33,8 +38,19 @@ pub enum PlayReq {
     V(f32),
 }
 
+/// 边下边播时至少缓冲这么多再开始
+const BUFFER: u64 = 512 * 1024;
+
+/// 后台缓冲好的歌曲, 交回主循环播放
+struct Buffered {
+    id: usize,
+    lyric: Lyric,
+    progress: Arc<Progress>,
+    decoder: Result<Decoder<GrowingFile>, String>,
+}
+
 pub struct PlayCtx {
-    pub nc: Arc<Nc>,
+    pub nc: Arc<dyn MusicApi>,
     pub event_tx: Sender<ES>,
     pub cancel: CancellationToken,
     pub config: Config,
@@ -43,9 +59,14 @@ pub struct PlayCtx {
 pub fn play(mut ctx: PlayCtx, sink: Sink) -> (impl Future<Output = ()>, UnboundedSender<PlayReq>) {
     // todo 为什么返回impl Future, 就不行呢
     let (tx, mut rx) = mpsc::unbounded_channel();
+    let (btx, mut brx) = mpsc::unbounded_channel::<Buffered>();
     (
         async move {
             let mut tick = tokio::time::interval(Duration::from_secs(1));
+            let mut downloading: HashMap<usize, Arc<Progress>> = HashMap::new();
+            let mut playing: Option<Arc<Progress>> = None;
+            // 正在缓冲的歌, 切歌后旧的缓冲结果直接丢掉
+            let mut pending: Option<usize> = None;
             loop {
                 select! {
                     req = rx.recv() => {
@@ -110,56 +131,108 @@ pub fn play(mut ctx: PlayCtx, sink: Sink) -> (impl Future<Output = ()>, Unbounde
                                             continue;
                                         }
                                     };
-                                    if !ctx.nc.song_cached(id) {
-                                        match ctx.nc.song_url(id).await {
-                                            Ok(song) => {
-                                                ctx.event_tx.send(ES::Tip(SimpleMsg("start down", Duration::from_secs(1))));
-                                                match ctx.nc
-                                                    .download(
-                                                        song.data[0].url.as_str(),
-                                                        id,
-                                                    )
-                                                    .await
-                                                {
-                                                    Ok(r) => r,
-                                                    Err(e) => {
-                                                        ctx.event_tx.wrap_error("download", &e);
-                                                        ctx.event_tx.send(ES::Play(Play::State(PlayState::Failed(id))));
-                                                        continue;
-                                                    }
-                                                };
-                                                ctx.event_tx.send(ES::Tip(SimpleMsg("down ok", Duration::from_secs(1))));
-
-                                            }
-                                            Err(e) => {
-                                                ctx.event_tx.wrap_error("req.song.url", &e);
-                                                ctx.event_tx.send(ES::Play(Play::State(PlayState::Failed(id))));
-                                                continue;
-                                            }
-                                        }
+                                    if let Some(p) = playing.take() {
+                                        p.close_readers();
                                     }
-
-                                    let file = match OpenOptions::new()
+                                    pending = None;
+                                    if let Some(path) = ctx.nc.song_file(id) {
+                                        let file = match OpenOptions::new()
                                             .read(true)
-                                            .open(ctx.config.Cache().join(format!("{}.mp3", id)))
+                                            .open(path)
                                         {
                                             Ok(file) => file,
                                             Err(e) => {
                                                 ctx.event_tx.wrap_error("load.song_file", &e);
                                                 ctx.event_tx.send(ES::Play(Play::State(PlayState::Failed(id))));
-                                                return;
+                                                continue;
                                             }
                                         };
                                         sink.clear();
                                         sink.append(rodio::Decoder::try_from(file).unwrap());
-                                        ctx.event_tx.send(ES::Play(Play::State(PlayState::Play(id, lyric.lrc.lyric))));
-                                        ctx.event_tx.send(ES::Play(Play::State(PlayState::Start)));
-                                        sink.play();
+                                    } else {
+                                        // 同一首歌还在下载就接着用, 不重复下载
+                                        let progress = match downloading.get(&id) {
+                                            Some(p) if !p.finished() => p.clone(),
+                                            _ => {
+                                                let (url, kind) = match ctx.nc.song_url(id).await {
+                                                    Ok(song) => (
+                                                        song.data[0].url.to_owned(),
+                                                        song.data[0]
+                                                            .kind
+                                                            .clone()
+                                                            .or(song.data[0].encode_type.clone())
+                                                            .unwrap_or_default(),
+                                                    ),
+                                                    Err(e) => {
+                                                        ctx.event_tx.wrap_error("req.song.url", &e);
+                                                        ctx.event_tx.send(ES::Play(Play::State(PlayState::Failed(id))));
+                                                        continue;
+                                                    }
+                                                };
+                                                let progress = Progress::new();
+                                                tokio::spawn({
+                                                    let nc = ctx.nc.clone();
+                                                    let tx = ctx.event_tx.clone();
+                                                    let progress = progress.clone();
+                                                    async move {
+                                                        if let Err(e) = nc.download(&url, id, &kind, progress).await {
+                                                            tx.wrap_error("download", &e);
+                                                        }
+                                                    }
+                                                });
+                                                downloading.retain(|_, p| !p.finished());
+                                                downloading.insert(id, progress.clone());
+                                                progress
+                                            }
+                                        };
+                                        // 缓冲放到后台, 不挡住暂停/切歌
+                                        pending = Some(id);
+                                        tokio::spawn({
+                                            let btx = btx.clone();
+                                            async move {
+                                                let decoder = match tokio::task::spawn_blocking({
+                                                    let progress = progress.clone();
+                                                    move || open_progressive(progress)
+                                                })
+                                                .await
+                                                {
+                                                    Ok(ret) => ret,
+                                                    Err(e) => Err(e.to_string()),
+                                                };
+                                                btx.send(Buffered { id, lyric, progress, decoder });
+                                            }
+                                        });
+                                        continue;
+                                    }
+                                    ctx.event_tx.send(ES::Play(Play::State(PlayState::Play(id, lyric))));
+                                    ctx.event_tx.send(ES::Play(Play::State(PlayState::Start)));
+                                    sink.play();
                                 }
                                 _ => {},
                             }
                          }
                     }
+                    Some(ready) = brx.recv() => {
+                        if pending != Some(ready.id) {
+                            continue;
+                        }
+                        pending = None;
+                        let id = ready.id;
+                        match ready.decoder {
+                            Ok(decoder) => {
+                                sink.clear();
+                                sink.append(decoder);
+                                playing = Some(ready.progress);
+                                ctx.event_tx.send(ES::Play(Play::State(PlayState::Play(id, ready.lyric))));
+                                ctx.event_tx.send(ES::Play(Play::State(PlayState::Start)));
+                                sink.play();
+                            }
+                            Err(e) => {
+                                ctx.event_tx.wrap_error("load.song_file", &e);
+                                ctx.event_tx.send(ES::Play(Play::State(PlayState::Failed(id))));
+                            }
+                        }
+                    }
                     _ = tick.tick() => {
                         ctx.event_tx.send(ES::SEC);
                     }
@@ -172,3 +245,19 @@ pub fn play(mut ctx: PlayCtx, sink: Sink) -> (impl Future<Output = ()>, Unbounde
         tx,
     )
 }
+
+// 等缓冲够了再解码, 会阻塞, 放在 spawn_blocking 里调用
+fn open_progressive(progress: Arc<Progress>) -> Result<Decoder<GrowingFile>, String> {
+    if !progress.wait(BUFFER) {
+        return Err("download failed".to_owned());
+    }
+    let file = progress.open().map_err(|e| e.to_string())?;
+    let mut builder = Decoder::builder().with_data(file).with_seekable(true);
+    if let Some(ext) = progress.path().extension().and_then(|v| v.to_str()) {
+        builder = builder.with_hint(ext);
+    }
+    if let Some(total) = progress.total() {
+        builder = builder.with_byte_len(total);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
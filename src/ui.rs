@@ -1,10 +1,14 @@
 pub mod about;
+pub mod album;
 pub mod app;
+pub mod artist;
+pub mod comment;
 pub mod content;
 pub mod focus;
 pub mod footer;
 pub mod head;
 pub mod search;
+pub mod similar;
 pub mod slide;
 pub mod widgets;
 pub mod zero;
@@ -0,0 +1,237 @@
+use std::borrow::Cow;
+
+use chrono::DateTime;
+use ratatui::{
+    crossterm::event::{Event, KeyCode},
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style, palette::tailwind::SLATE},
+    text::Line,
+    widgets::{
+        Block, Clear, List, ListItem, ListState, Paragraph, StatefulWidgetRef, Widget, WidgetRef,
+    },
+};
+
+use crate::{
+    event::{ES, Play, PlayListOP, PlayMode},
+    m163::typ::{AlbumInfo, PlayItem},
+    play::PlayReq,
+    ui::{
+        app::{Modal, ShareCtx, Wrap, global_help},
+        artist::open_artist,
+        footer::add_music_to_play,
+        widgets::help::Help,
+    },
+};
+
+/// 专辑页: 信息与曲目
+pub struct Album {
+    id: usize,
+    info: Option<AlbumInfo>,
+    songs: Vec<PlayItem>,
+    list_state: ListState,
+    close: bool,
+    ctx: ShareCtx,
+}
+
+impl Album {
+    pub fn new(ctx: ShareCtx, id: usize) -> Self {
+        ctx.borrow().rt.spawn({
+            let aux = ctx.borrow().async_clone();
+            async move {
+                match aux.nc.album(id).await {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataAlbum(id, d));
+                    }
+                    Err(e) => aux.tx.wrap_error("album", &e),
+                }
+            }
+        });
+        Album {
+            id,
+            info: None,
+            songs: vec![],
+            list_state: ListState::default(),
+            close: false,
+            ctx,
+        }
+    }
+
+    fn selected(&self) -> Option<&PlayItem> {
+        self.list_state.selected().and_then(|v| self.songs.get(v))
+    }
+
+    fn play_all(&self, mode: PlayMode) {
+        if self.songs.is_empty() {
+            return;
+        }
+        self.ctx.borrow().tx.send(ES::Play(Play::PlayMode(mode)));
+        self.ctx.borrow().tx.send(ES::Play(Play::PlayList((
+            self.songs.clone(),
+            PlayListOP::Set,
+        ))));
+        self.ctx.borrow().ptx.send(PlayReq::Play(self.songs[0].id));
+    }
+}
+
+/// 打开歌曲所属专辑的页面
+pub fn open_album(ctx: ShareCtx, item: &PlayItem) {
+    match item.al.as_ref().filter(|v| v.id != 0) {
+        Some(v) => {
+            let album = Album::new(ctx.clone(), v.id);
+            ctx.borrow_mut().add_modal(album);
+        }
+        None => ctx.borrow_mut().info("没有专辑信息"),
+    }
+}
+
+impl Modal for Album {
+    fn render_ref(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let block = Block::bordered().title(Cow::Borrowed("专辑"));
+        let layouts = Layout::new(
+            Direction::Horizontal,
+            vec![
+                Constraint::Percentage(20),
+                Constraint::Fill(1),
+                Constraint::Percentage(20),
+            ],
+        )
+        .split(area);
+
+        let inner = block.inner(layouts[1]);
+        Clear.render(layouts[1], buf);
+        block.render(layouts[1], buf);
+        let ss = Layout::vertical(vec![Constraint::Length(6), Constraint::Fill(1)]).split(inner);
+        match &self.info {
+            Some(info) => {
+                let ctx = self.ctx.borrow();
+                Paragraph::new(format!(
+                    "{} / {}  {}首 {}\n{}",
+                    ctx.maybe_hidden(info.name.as_str()),
+                    ctx.maybe_hidden(
+                        info.artist
+                            .as_ref()
+                            .and_then(|v| v.name.as_deref())
+                            .unwrap_or("")
+                    ),
+                    info.size,
+                    DateTime::from_timestamp_millis(info.publish_time)
+                        .map(|v| v.format("%Y-%m-%d").to_string())
+                        .unwrap_or("-".to_owned()),
+                    info.description
+                        .as_ref()
+                        .map(|v| ctx.maybe_hidden(v.as_str()))
+                        .unwrap_or_default(),
+                ))
+                .wrap(ratatui::widgets::Wrap { trim: true })
+                .block(Block::bordered().title("信息"))
+                .render_ref(ss[0], buf);
+            }
+            None => "loading".render_ref(ss[0], buf),
+        }
+        StatefulWidgetRef::render_ref(
+            &(List::new(
+                self.songs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        let mut style = Style::default();
+                        if self.ctx.borrow().offline && self.ctx.borrow().nc.song_cached(v.id) {
+                            style = style.fg(ratatui::style::Color::Red);
+                        }
+                        ListItem::new(Line::styled(
+                            format!(
+                                "{:>2}. {}",
+                                i + 1,
+                                self.ctx.borrow().maybe_hidden(v.name.as_str())
+                            ),
+                            style,
+                        ))
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">")),
+            ss[1],
+            buf,
+            &mut self.list_state,
+        );
+    }
+
+    fn event(&mut self, e: &mut crate::event::ES) -> bool {
+        match e {
+            ES::DataAlbum(id, d) => {
+                if *id == self.id {
+                    self.info = Some(d.album.clone());
+                    self.songs = std::mem::take(&mut d.songs);
+                    if !self.songs.is_empty() {
+                        self.list_state.select_first();
+                    }
+                }
+            }
+            ES::Event(Event::Key(k)) => match k.code {
+                KeyCode::Char('h') => {
+                    self.ctx.borrow_mut().add_modal(Help::new(global_help(vec![
+                        ("esc".to_owned(), "返回上一级".to_owned()),
+                        ("j/k".to_owned(), "下/上移动".to_owned()),
+                        ("enter".to_owned(), "播放".to_owned()),
+                        ("t".to_owned(), "加入到某个歌单".to_owned()),
+                        ("g".to_owned(), "查看歌手".to_owned()),
+                        ("r".to_owned(), "随机播放专辑".to_owned()),
+                        ("o".to_owned(), "列表播放专辑".to_owned()),
+                    ])));
+                    return false;
+                }
+                KeyCode::Char('k') => {
+                    self.list_state.select_previous();
+                    return false;
+                }
+                KeyCode::Char('j') => {
+                    self.list_state.select_next();
+                    return false;
+                }
+                KeyCode::Char('t') => {
+                    if let Some(v) = self.selected() {
+                        add_music_to_play(self.ctx.clone(), v.id);
+                    }
+                    return false;
+                }
+                KeyCode::Char('g') => {
+                    if let Some(v) = self.selected() {
+                        open_artist(self.ctx.clone(), v);
+                    }
+                    return false;
+                }
+                KeyCode::Char('r') => {
+                    self.play_all(PlayMode::Random);
+                    return false;
+                }
+                KeyCode::Char('o') => {
+                    self.play_all(PlayMode::Order);
+                    return false;
+                }
+                KeyCode::Enter => {
+                    if let Some(v) = self.selected().cloned() {
+                        let id = v.id;
+                        self.ctx
+                            .borrow()
+                            .tx
+                            .send(ES::Play(Play::PlayList((vec![v], PlayListOP::Append))));
+                        self.ctx.borrow().ptx.send(PlayReq::Play(id));
+                    }
+                    return false;
+                }
+                KeyCode::Esc => {
+                    self.close = true;
+                    return false;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        true
+    }
+
+    fn closed(&self) -> bool {
+        self.close
+    }
+}
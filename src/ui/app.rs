@@ -22,15 +22,12 @@ use tui_qrcode::QrCodeWidget;
 use crate::{
     config::{Config, load},
     event::{AppState, ES, HeadMenuKey, LoginState},
-    m163::{
-        self,
-        client::{Nc, TARGET},
-    },
+    m163::{self, api::MusicApi},
     play::PlayReq,
     ui::{
         content::Content,
         focus::Focus,
-        footer::Footer,
+        footer::{Footer, LyricSub},
         head::Head,
         search::Search,
         slide::Slide,
@@ -42,7 +39,7 @@ use crate::{
 };
 
 pub struct Context {
-    pub nc: Arc<m163::client::Nc>,
+    pub nc: Arc<dyn MusicApi>,
     pub tx: mpsc::Sender<ES>,
     pub rt: Arc<tokio::runtime::Runtime>,
     pub ptx: UnboundedSender<PlayReq>,
@@ -53,6 +50,8 @@ pub struct Context {
     pub modals: Vec<Rc<RefCell<InnerModal>>>,
     pub offline: bool,
     pub private: bool,
+    /// 歌词下方显示翻译或音译
+    pub lyric_sub: LyricSub,
 }
 
 struct InnerModal {
@@ -62,7 +61,7 @@ struct InnerModal {
 
 #[derive(Clone)]
 pub struct AsyncUtil {
-    pub nc: Arc<m163::client::Nc>,
+    pub nc: Arc<dyn MusicApi>,
     pub tx: mpsc::Sender<ES>,
     pub rt: Arc<tokio::runtime::Runtime>,
     pub ptx: UnboundedSender<PlayReq>,
@@ -135,8 +134,10 @@ pub fn global_help(mut base: Vec<(String, String)>) -> Vec<(String, String)> {
         ("-/+".to_owned(), "调整音量".to_owned()),
         ("f".to_owned(), "搜索".to_owned()),
         ("z".to_owned(), "纯净模式".to_owned()),
+        ("X".to_owned(), "私人FM: 不喜欢并跳过".to_owned()),
         ("a".to_owned(), "登出".to_owned()),
         ("i".to_owned(), "隐私模式".to_owned()),
+        ("y".to_owned(), "歌词: 原文/翻译/音译".to_owned()),
     ]);
     base.append(&mut any_help());
     base
@@ -169,7 +170,7 @@ pub struct App {
 
 impl App {
     pub fn new(
-        nc: Arc<Nc>,
+        nc: Arc<dyn MusicApi>,
         event_tx: Sender<ES>,
         play_tx: UnboundedSender<PlayReq>,
         config: Config,
@@ -192,6 +193,7 @@ impl App {
             modals: vec![],
             offline: false,
             private: false,
+            lyric_sub: LyricSub::None,
         }));
 
         let top = ctx.clone();
@@ -409,6 +411,17 @@ impl App {
             ES::LoginState(s) => {
                 self.login_state = s.clone();
             }
+            ES::DataLikeList(list) => {
+                self.ctx.borrow_mut().like_set = list.ids.iter().copied().collect();
+            }
+            ES::Liked(id, like) => {
+                let mut ctx = self.ctx.borrow_mut();
+                if *like {
+                    ctx.like_set.insert(*id);
+                } else {
+                    ctx.like_set.remove(id);
+                }
+            }
             ES::AppState(s) => {
                 self.state = s.clone();
                 match s {
@@ -426,10 +439,22 @@ impl App {
                                             KeyModifiers::empty(),
                                         )))); // todo remove replace by head method
                                         txx.send(ES::DataProfile(v));
+                                        match nnx.like_list().await {
+                                            Ok(v) => {
+                                                txx.send(ES::DataLikeList(v));
+                                            }
+                                            Err(e) => txx.wrap_error("like_list", &e),
+                                        }
                                     }
                                     Err(err) => {
                                         match err {
                                             m163::client::NCErr::Offline => {}
+                                            // nc 已经切到了认证状态
+                                            m163::client::NCErr::LoginRequired(_) => {}
+                                            // 下次请求会等退避结束, 直接重试
+                                            m163::client::NCErr::RateLimited(_) => {
+                                                txx.send(ES::AppState(AppState::Authed));
+                                            }
                                             _ => {
                                                 // 获取不到就重新认证
                                                 txx.send(ES::AppState(AppState::Authing));
@@ -469,7 +494,7 @@ impl App {
                 self.login_qr = Some(
                     QrCode::new(format!(
                         "{}/login?codekey={}&chainId={}",
-                        TARGET,
+                        self.ctx.borrow().config.base_url(),
                         l,
                         self.login_chain.as_str()
                     ))
@@ -0,0 +1,343 @@
+use std::borrow::Cow;
+
+use chrono::DateTime;
+use ratatui::{
+    crossterm::event::{Event, KeyCode},
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style, palette::tailwind::SLATE},
+    text::Line,
+    widgets::{
+        Block, Clear, List, ListItem, ListState, Paragraph, StatefulWidgetRef, Tabs, Widget,
+        WidgetRef,
+    },
+};
+
+use crate::{
+    event::{ES, Play, PlayListOP, PlayMode},
+    m163::typ::{ArtistAlbum, ArtistInfo, PlayItem},
+    play::PlayReq,
+    ui::{
+        album::{Album, open_album},
+        app::{Modal, ShareCtx, Wrap, global_help},
+        footer::add_music_to_play,
+        widgets::help::Help,
+    },
+};
+
+/// 专辑每页条数
+const PAGE: usize = 30;
+
+const TABS: [&str; 2] = ["热门单曲", "专辑"];
+
+/// 歌手页: 简介, 热门单曲与专辑
+pub struct Artist {
+    id: usize,
+    info: Option<ArtistInfo>,
+    songs: Vec<PlayItem>,
+    albums: Vec<ArtistAlbum>,
+    more: bool,
+    loading: bool,
+    tab: usize,
+    list_state: ListState,
+    close: bool,
+    ctx: ShareCtx,
+}
+
+impl Artist {
+    pub fn new(ctx: ShareCtx, id: usize) -> Self {
+        let mut artist = Artist {
+            id,
+            info: None,
+            songs: vec![],
+            albums: vec![],
+            more: false,
+            loading: false,
+            tab: 0,
+            list_state: ListState::default(),
+            close: false,
+            ctx,
+        };
+        artist.ctx.borrow().rt.spawn({
+            let aux = artist.ctx.borrow().async_clone();
+            async move {
+                match aux.nc.artist(id).await {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataArtist(id, d));
+                    }
+                    Err(e) => aux.tx.wrap_error("artist", &e),
+                }
+                match aux.nc.artist_songs(id).await {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataArtistSongs(id, d));
+                    }
+                    Err(e) => aux.tx.wrap_error("artist_songs", &e),
+                }
+            }
+        });
+        artist.load_albums();
+        artist
+    }
+
+    fn load_albums(&mut self) {
+        self.loading = true;
+        let (id, offset) = (self.id, self.albums.len());
+        self.ctx.borrow().rt.spawn({
+            let aux = self.ctx.borrow().async_clone();
+            async move {
+                match aux.nc.artist_albums(id, offset, PAGE).await {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataArtistAlbums(id, offset, d));
+                    }
+                    Err(e) => {
+                        aux.tx.send(ES::DataArtistAlbumsFailed(id, offset));
+                        aux.tx.wrap_error("artist_albums", &e);
+                    }
+                }
+            }
+        });
+    }
+
+    fn len(&self) -> usize {
+        if self.tab == 0 {
+            self.songs.len()
+        } else {
+            self.albums.len()
+        }
+    }
+
+    fn selected_song(&self) -> Option<&PlayItem> {
+        if self.tab != 0 {
+            return None;
+        }
+        self.list_state.selected().and_then(|v| self.songs.get(v))
+    }
+
+    fn rows(&self) -> Vec<String> {
+        if self.tab == 0 {
+            self.songs
+                .iter()
+                .map(|v| self.ctx.borrow().maybe_hidden(v.name.as_str()))
+                .collect()
+        } else {
+            self.albums
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{} ({}首) {}",
+                        self.ctx.borrow().maybe_hidden(v.name.as_str()),
+                        v.size,
+                        DateTime::from_timestamp_millis(v.publish_time)
+                            .map(|v| v.format("%Y-%m-%d").to_string())
+                            .unwrap_or("-".to_owned())
+                    )
+                })
+                .collect()
+        }
+    }
+
+    fn play_all(&self, mode: PlayMode) {
+        if self.songs.is_empty() {
+            return;
+        }
+        self.ctx.borrow().tx.send(ES::Play(Play::PlayMode(mode)));
+        self.ctx.borrow().tx.send(ES::Play(Play::PlayList((
+            self.songs.clone(),
+            PlayListOP::Set,
+        ))));
+        self.ctx.borrow().ptx.send(PlayReq::Play(self.songs[0].id));
+    }
+
+    fn enter(&mut self) {
+        let Some(index) = self.list_state.selected().filter(|v| *v < self.len()) else {
+            return;
+        };
+        if self.tab == 0 {
+            let item = self.songs[index].clone();
+            let id = item.id;
+            self.ctx
+                .borrow()
+                .tx
+                .send(ES::Play(Play::PlayList((vec![item], PlayListOP::Append))));
+            self.ctx.borrow().ptx.send(PlayReq::Play(id));
+        } else {
+            let album = Album::new(self.ctx.clone(), self.albums[index].id);
+            self.ctx.borrow_mut().add_modal(album);
+        }
+    }
+}
+
+/// 打开歌曲第一个歌手的页面
+pub fn open_artist(ctx: ShareCtx, item: &PlayItem) {
+    match item.art_r.iter().find(|v| v.id != 0) {
+        Some(v) => {
+            let artist = Artist::new(ctx.clone(), v.id);
+            ctx.borrow_mut().add_modal(artist);
+        }
+        None => ctx.borrow_mut().info("没有歌手信息"),
+    }
+}
+
+impl Modal for Artist {
+    fn render_ref(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let block = Block::bordered().title(Cow::Borrowed("歌手"));
+        let layouts = Layout::new(
+            Direction::Horizontal,
+            vec![
+                Constraint::Percentage(20),
+                Constraint::Fill(1),
+                Constraint::Percentage(20),
+            ],
+        )
+        .split(area);
+
+        let inner = block.inner(layouts[1]);
+        Clear.render(layouts[1], buf);
+        block.render(layouts[1], buf);
+        let ss = Layout::vertical(vec![
+            Constraint::Length(6),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .split(inner);
+        match &self.info {
+            Some(info) => {
+                let ctx = self.ctx.borrow();
+                Paragraph::new(format!(
+                    "{}  专辑:{} 单曲:{}\n{}",
+                    ctx.maybe_hidden(info.name.as_str()),
+                    info.album_size,
+                    info.music_size,
+                    info.brief_desc
+                        .as_ref()
+                        .map(|v| ctx.maybe_hidden(v.as_str()))
+                        .unwrap_or_default(),
+                ))
+                .wrap(ratatui::widgets::Wrap { trim: true })
+                .block(Block::bordered().title("信息"))
+                .render_ref(ss[0], buf);
+            }
+            None => "loading".render_ref(ss[0], buf),
+        }
+        Tabs::new(TABS)
+            .select(self.tab)
+            .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+            .render(ss[1], buf);
+        StatefulWidgetRef::render_ref(
+            &(List::new(
+                self.rows()
+                    .into_iter()
+                    .map(|v| ListItem::new(Line::styled(v, Style::default())))
+                    .collect::<Vec<_>>(),
+            )
+            .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">")),
+            ss[2],
+            buf,
+            &mut self.list_state,
+        );
+    }
+
+    fn event(&mut self, e: &mut crate::event::ES) -> bool {
+        match e {
+            ES::DataArtist(id, d) => {
+                if *id == self.id {
+                    self.info = Some(d.artist.clone());
+                    // 热门 50 首还没到时先用详情里带的
+                    if self.songs.is_empty() {
+                        self.songs = std::mem::take(&mut d.hot_songs);
+                    }
+                }
+            }
+            ES::DataArtistSongs(id, d) => {
+                if *id == self.id && !d.songs.is_empty() {
+                    self.songs = std::mem::take(&mut d.songs);
+                    if self.tab == 0 && self.list_state.selected().is_none() {
+                        self.list_state.select_first();
+                    }
+                }
+            }
+            ES::DataArtistAlbums(id, offset, d) => {
+                if *id == self.id && *offset == self.albums.len() {
+                    self.loading = false;
+                    self.more = d.more;
+                    self.albums.append(&mut d.albums);
+                }
+            }
+            ES::DataArtistAlbumsFailed(id, offset) => {
+                if *id == self.id && *offset == self.albums.len() {
+                    self.loading = false;
+                }
+            }
+            ES::Event(Event::Key(k)) => match k.code {
+                KeyCode::Char('h') => {
+                    self.ctx.borrow_mut().add_modal(Help::new(global_help(vec![
+                        ("esc".to_owned(), "返回上一级".to_owned()),
+                        ("tab".to_owned(), "切换单曲/专辑".to_owned()),
+                        ("j/k".to_owned(), "下/上移动, 专辑到底加载更多".to_owned()),
+                        ("enter".to_owned(), "播放".to_owned()),
+                        ("t".to_owned(), "加入到某个歌单".to_owned()),
+                        ("r".to_owned(), "随机播放热门单曲".to_owned()),
+                        ("o".to_owned(), "列表播放热门单曲".to_owned()),
+                    ])));
+                    return false;
+                }
+                KeyCode::Char('k') => {
+                    self.list_state.select_previous();
+                    return false;
+                }
+                KeyCode::Char('j') => {
+                    let at_end = self
+                        .list_state
+                        .selected()
+                        .is_some_and(|v| v + 1 >= self.len());
+                    if self.tab == 1 && at_end && self.more && !self.loading {
+                        self.load_albums();
+                    }
+                    self.list_state.select_next();
+                    return false;
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    self.tab = (self.tab + 1) % TABS.len();
+                    self.list_state
+                        .select(if self.len() > 0 { Some(0) } else { None });
+                    return false;
+                }
+                KeyCode::Char('t') => {
+                    if let Some(v) = self.selected_song() {
+                        add_music_to_play(self.ctx.clone(), v.id);
+                    }
+                    return false;
+                }
+                KeyCode::Char('b') => {
+                    if let Some(v) = self.selected_song() {
+                        open_album(self.ctx.clone(), v);
+                    }
+                    return false;
+                }
+                KeyCode::Char('r') => {
+                    self.play_all(PlayMode::Random);
+                    return false;
+                }
+                KeyCode::Char('o') => {
+                    self.play_all(PlayMode::Order);
+                    return false;
+                }
+                KeyCode::Enter => {
+                    self.enter();
+                    return false;
+                }
+                KeyCode::Esc => {
+                    self.close = true;
+                    return false;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        true
+    }
+
+    fn closed(&self) -> bool {
+        self.close
+    }
+}
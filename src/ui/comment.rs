@@ -0,0 +1,268 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    crossterm::event::{Event, KeyCode},
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style, palette::tailwind::SLATE},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, Clear, List, ListItem, ListState, StatefulWidgetRef, Tabs, Widget, WidgetRef,
+    },
+};
+
+use crate::{
+    event::ES,
+    m163::typ::{Comment, CommentType},
+    ui::{
+        app::{Modal, ShareCtx, Wrap, global_help},
+        widgets::help::Help,
+    },
+};
+
+const TABS: [&str; 2] = ["热门评论", "最新评论"];
+
+#[derive(Default)]
+struct Page {
+    list: Vec<Comment>,
+    total: usize,
+    more: bool,
+    loading: bool,
+}
+
+/// 歌曲或歌单的评论
+pub struct Comments {
+    kind: CommentType,
+    id: usize,
+    title: String,
+    pages: [Page; 2],
+    tab: usize,
+    list_state: ListState,
+    close: bool,
+    ctx: ShareCtx,
+}
+
+impl Comments {
+    pub fn new(ctx: ShareCtx, kind: CommentType, id: usize, title: String) -> Self {
+        let mut comments = Comments {
+            kind,
+            id,
+            title,
+            pages: Default::default(),
+            tab: 0,
+            list_state: ListState::default(),
+            close: false,
+            ctx,
+        };
+        comments.load(0);
+        comments.load(1);
+        comments
+    }
+
+    fn load(&mut self, tab: usize) {
+        let page = &mut self.pages[tab];
+        page.loading = true;
+        let (kind, id, offset) = (self.kind, self.id, page.list.len());
+        // 最新评论用最后一条的时间翻页
+        let before = page.list.last().map(|v| v.time).unwrap_or(0);
+        let hot = tab == 0;
+        self.ctx.borrow().rt.spawn({
+            let aux = self.ctx.borrow().async_clone();
+            async move {
+                let ret = if hot {
+                    aux.nc.hot_comments(kind, id, offset).await
+                } else {
+                    aux.nc.comments(kind, id, offset, before).await
+                };
+                match ret {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataComments(id, hot, offset, d));
+                    }
+                    Err(e) => {
+                        aux.tx.send(ES::DataCommentsFailed(id, hot, offset));
+                        aux.tx.wrap_error("comments", &e);
+                    }
+                }
+            }
+        });
+    }
+
+    fn items(&self, width: usize) -> Vec<ListItem<'static>> {
+        let ctx = self.ctx.borrow();
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        self.pages[self.tab]
+            .list
+            .iter()
+            .map(|v| {
+                let mut lines = vec![Line::styled(
+                    format!(
+                        "{}  赞 {}  {}",
+                        ctx.maybe_hidden(v.user.nickname.as_str()),
+                        v.liked_count,
+                        DateTime::from_timestamp_millis(v.time)
+                            .map(|v| v.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or("-".to_owned())
+                    ),
+                    dim,
+                )];
+                let content = ctx.maybe_hidden(v.content.as_str());
+                for line in content.lines() {
+                    lines.extend(wrap(line, width).into_iter().map(Line::raw));
+                }
+                lines.push(Line::raw(""));
+                ListItem::new(Text::from(lines))
+            })
+            .collect()
+    }
+}
+
+/// 按显示宽度折行, 中文占两格
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut ret = vec![];
+    let mut line = String::new();
+    let mut w = 0;
+    for c in text.chars() {
+        let cw = Span::raw(c.to_string()).width();
+        if w + cw > width && !line.is_empty() {
+            ret.push(std::mem::take(&mut line));
+            w = 0;
+        }
+        line.push(c);
+        w += cw;
+    }
+    ret.push(line);
+    ret
+}
+
+/// 打开评论页面
+pub fn open_comments(ctx: ShareCtx, kind: CommentType, id: usize, title: &str) {
+    let comments = Comments::new(ctx.clone(), kind, id, title.to_owned());
+    ctx.borrow_mut().add_modal(comments);
+}
+
+impl Modal for Comments {
+    fn render_ref(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let title = format!(
+            "评论: {}",
+            self.ctx.borrow().maybe_hidden(self.title.as_str())
+        );
+        let block = Block::bordered().title(Cow::Owned(title));
+        let layouts = Layout::new(
+            Direction::Horizontal,
+            vec![
+                Constraint::Percentage(20),
+                Constraint::Fill(1),
+                Constraint::Percentage(20),
+            ],
+        )
+        .split(area);
+
+        let inner = block.inner(layouts[1]);
+        Clear.render(layouts[1], buf);
+        block.render(layouts[1], buf);
+        let ss = Layout::vertical(vec![Constraint::Length(1), Constraint::Fill(1)]).split(inner);
+        Tabs::new(
+            TABS.iter()
+                .zip(self.pages.iter())
+                .map(|(t, p)| format!("{}({})", t, p.total)),
+        )
+        .select(self.tab)
+        .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+        .render(ss[0], buf);
+        let page = &self.pages[self.tab];
+        if page.loading && page.list.is_empty() {
+            "loading".render_ref(ss[1], buf);
+            return;
+        }
+        // 留出高亮符号的一格
+        let items = self.items((ss[1].width as usize).saturating_sub(1).max(1));
+        StatefulWidgetRef::render_ref(
+            &(List::new(items)
+                .highlight_style(Style::new().bg(SLATE.c800))
+                .highlight_symbol(">")),
+            ss[1],
+            buf,
+            &mut self.list_state,
+        );
+    }
+
+    fn event(&mut self, e: &mut crate::event::ES) -> bool {
+        match e {
+            ES::DataComments(id, hot, offset, d) => {
+                let tab = if *hot { 0 } else { 1 };
+                if *id == self.id && *offset == self.pages[tab].list.len() {
+                    let page = &mut self.pages[tab];
+                    page.loading = false;
+                    page.more = d.more;
+                    page.total = d.total;
+                    page.list.append(if *hot {
+                        &mut d.hot_comments
+                    } else {
+                        &mut d.comments
+                    });
+                    if self.tab == tab && self.list_state.selected().is_none() {
+                        self.list_state.select_first();
+                    }
+                }
+            }
+            ES::DataCommentsFailed(id, hot, offset) => {
+                let page = &mut self.pages[if *hot { 0 } else { 1 }];
+                if *id == self.id && *offset == page.list.len() {
+                    page.loading = false;
+                }
+            }
+            ES::Event(Event::Key(k)) => match k.code {
+                KeyCode::Char('h') => {
+                    self.ctx.borrow_mut().add_modal(Help::new(global_help(vec![
+                        ("esc".to_owned(), "返回上一级".to_owned()),
+                        ("tab".to_owned(), "切换热门/最新".to_owned()),
+                        ("j/k".to_owned(), "下/上移动, 到底加载更多".to_owned()),
+                    ])));
+                    return false;
+                }
+                KeyCode::Char('k') => {
+                    self.list_state.select_previous();
+                    return false;
+                }
+                KeyCode::Char('j') => {
+                    let page = &self.pages[self.tab];
+                    let at_end = self
+                        .list_state
+                        .selected()
+                        .is_some_and(|v| v + 1 >= page.list.len());
+                    if at_end && page.more && !page.loading {
+                        self.load(self.tab);
+                    }
+                    self.list_state.select_next();
+                    return false;
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    self.tab = (self.tab + 1) % TABS.len();
+                    // 首页加载失败过, 切回来时重新拉
+                    let page = &self.pages[self.tab];
+                    if page.list.is_empty() && !page.loading {
+                        self.load(self.tab);
+                    }
+                    self.list_state
+                        .select(if self.pages[self.tab].list.is_empty() {
+                            None
+                        } else {
+                            Some(0)
+                        });
+                    return false;
+                }
+                KeyCode::Esc => {
+                    self.close = true;
+                    return false;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        true
+    }
+
+    fn closed(&self) -> bool {
+        self.close
+    }
+}
@@ -18,12 +18,16 @@ use tokio::io::join;
 
 use crate::{
     event::{ES, Play, PlayListOP, PlayMode},
-    m163::typ::{PlayDetail, PlayDetailInner, PlayItem, PlayList},
+    m163::typ::{CommentType, PlayDetail, PlayDetailInner, PlayItem, PlayList},
     play::PlayReq,
     ui::{
+        album::open_album,
         app::{ShareCtx, global_help},
+        artist::open_artist,
+        comment::open_comments,
         focus::Focus,
-        footer::add_music_to_play,
+        footer::{add_music_to_play, toggle_like},
+        similar::open_similar,
         widgets::{help::Help, play_list::PlayList as PlayListWidget, tip::Msg},
     },
 };
@@ -53,6 +57,11 @@ impl Content {
             }
         }
     }
+    fn selected_track(&self) -> Option<&PlayItem> {
+        self.list
+            .as_ref()
+            .and_then(|v| v.playlist.tracks.get(self.list_state.selected()?))
+    }
     fn is_like(&self) -> bool {
         self.list
             .as_ref()
@@ -62,14 +71,15 @@ impl Content {
     pub fn event(&mut self, e: &mut ES) {
         match e {
             ES::DataPlayListDetail(pl) => {
+                // 大歌单会分批到达, 同一个歌单保留当前选中
+                let same = self
+                    .list
+                    .as_ref()
+                    .is_some_and(|v| v.playlist.id == pl.playlist.id);
                 self.list = Some(pl.clone());
-                if self.is_like() {
-                    self.ctx.borrow_mut().like_set.clear();
-                    for v in &self.list.as_ref().unwrap().playlist.tracks {
-                        self.ctx.borrow_mut().like_set.insert(v.id);
-                    }
+                if !same || self.list_state.selected().is_none() {
+                    self.init();
                 }
-                self.init();
             }
             ES::DataRecommendSongs(ss) => {
                 self.list = Some(PlayDetail {
@@ -86,6 +96,7 @@ impl Content {
                         description: None,
                         create_time: 0,
                         tags: vec![],
+                        track_ids: vec![],
                         comment_count: 0,
                         play_count: 0,
                         cover_img_url: "".to_owned(),
@@ -97,6 +108,7 @@ impl Content {
                                 id: v.id,
                                 dt: v.duration,
                                 art_r: v.artists.clone(),
+                                al: v.album.clone(),
                             })
                             .collect(),
                     },
@@ -114,9 +126,16 @@ impl Content {
                                 self.ctx.borrow_mut().add_modal(Help::new(global_help(vec![
                                     ("d".to_owned(), "从歌单移除歌曲".to_owned()),
                                     ("t".to_owned(), "加入到某个歌单".to_owned()),
+                                    ("g".to_owned(), "查看歌手".to_owned()),
+                                    ("b".to_owned(), "查看专辑".to_owned()),
+                                    ("w".to_owned(), "相似歌曲/歌单".to_owned()),
+                                    ("c".to_owned(), "歌曲评论".to_owned()),
+                                    ("C".to_owned(), "歌单评论".to_owned()),
+                                    ("l".to_owned(), "喜欢/取消喜欢".to_owned()),
                                     ("s".to_owned(), "收藏歌单".to_owned()),
                                     ("r".to_owned(), "随机播放歌单".to_owned()),
                                     ("o".to_owned(), "列表播放歌单".to_owned()),
+                                    ("m".to_owned(), "心动模式(我喜欢的音乐)".to_owned()),
                                     ("j/k".to_owned(), "下/上移动".to_owned()),
                                     ("esc".to_owned(), "返回上一级".to_owned()),
                                     ("enter".to_owned(), "播放".to_owned()),
@@ -154,7 +173,15 @@ impl Content {
                                                             Duration::from_millis(1500),
                                                         )));
                                                         aux.nc.clear_play_list(pid);
-                                                        match aux.nc.play_detail(pid).await {
+                                                        match aux
+                                                            .nc
+                                                            .play_detail(pid, &|d| {
+                                                                aux.tx.send(
+                                                                    ES::DataPlayListDetail(d),
+                                                                );
+                                                            })
+                                                            .await
+                                                        {
                                                             Ok(d) => {
                                                                 aux.tx.send(
                                                                     ES::DataPlayListDetail(d),
@@ -173,6 +200,47 @@ impl Content {
                                 );
                             }
                         }
+                        KeyCode::Char('l') => {
+                            if let Some(v) = self.selected_track() {
+                                toggle_like(self.ctx.clone(), v.id);
+                            }
+                        }
+                        KeyCode::Char('b') => {
+                            if let Some(v) = self.selected_track() {
+                                open_album(self.ctx.clone(), v);
+                            }
+                        }
+                        KeyCode::Char('c') => {
+                            if let Some(v) = self.selected_track() {
+                                open_comments(
+                                    self.ctx.clone(),
+                                    CommentType::Song,
+                                    v.id,
+                                    v.name.as_str(),
+                                );
+                            }
+                        }
+                        KeyCode::Char('C') => {
+                            // 日推, 新歌与专辑不是歌单
+                            if let Some(v) = self.list.as_ref().filter(|v| v.playlist.id != 0) {
+                                open_comments(
+                                    self.ctx.clone(),
+                                    CommentType::PlayList,
+                                    v.playlist.id,
+                                    v.playlist.name.as_str(),
+                                );
+                            }
+                        }
+                        KeyCode::Char('w') => {
+                            if let Some(v) = self.selected_track() {
+                                open_similar(self.ctx.clone(), v);
+                            }
+                        }
+                        KeyCode::Char('g') => {
+                            if let Some(v) = self.selected_track() {
+                                open_artist(self.ctx.clone(), v);
+                            }
+                        }
                         KeyCode::Char('t') => {
                             if self.list_state.selected().is_none() {
                                 return;
@@ -250,6 +318,33 @@ impl Content {
                                 self.list.as_ref().unwrap().playlist.tracks[0].id,
                             ));
                         }
+                        KeyCode::Char('m') => {
+                            if !self.is_like() {
+                                return;
+                            }
+                            let Some(play_list) = self.list.as_ref() else {
+                                return;
+                            };
+                            // 以选中的歌为种子, 没选中用第一首
+                            let Some(seed) = play_list
+                                .playlist
+                                .tracks
+                                .get(self.list_state.selected().unwrap_or(0))
+                                .cloned()
+                            else {
+                                return;
+                            };
+                            let (pid, id) = (play_list.playlist.id, seed.id);
+                            self.ctx
+                                .borrow()
+                                .tx
+                                .send(ES::Play(Play::PlayMode(PlayMode::Intelligence(pid, id))));
+                            self.ctx.borrow().tx.send(ES::Play(Play::PlayList((
+                                vec![seed],
+                                PlayListOP::Set,
+                            ))));
+                            self.ctx.borrow().ptx.send(PlayReq::Play(id));
+                        }
                         KeyCode::Char('k') => {
                             self.list_state.select_previous();
                         }
@@ -300,7 +395,7 @@ impl Content {
         match &self.list {
             Some(pl) => {
                 Paragraph::new(format!(
-                    r#"标签[{}] {}
+                    r#"标签[{}] {}{}{}
 作者:{} 签名:{}
 {}
 "#,
@@ -312,6 +407,20 @@ impl Content {
                             .map(|v| v.format("%Y-%m-%d").to_string())
                             .unwrap_or("-".to_owned())
                     },
+                    if pl.playlist.comment_count > 0 {
+                        format!(" 评论:{}", pl.playlist.comment_count)
+                    } else {
+                        "".to_owned()
+                    },
+                    if pl.playlist.tracks.len() < pl.playlist.track_ids.len() {
+                        format!(
+                            " 加载中 {}/{}",
+                            pl.playlist.tracks.len(),
+                            pl.playlist.track_ids.len()
+                        )
+                    } else {
+                        "".to_owned()
+                    },
                     self.ctx
                         .borrow()
                         .maybe_hidden(pl.playlist.creator.nickname.as_str()),
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     ops::{Add, Index, Sub},
     time::Duration,
 };
@@ -16,11 +16,15 @@ use ratatui::{
 
 use crate::{
     event::{ES, Play, PlayListOP, PlayMode, PlayState},
-    m163::typ::PlayItem,
+    m163::typ::{self, CommentType, Intelligence, PersonalFm, PlayItem},
     play::PlayReq,
     ui::{
-        app::{ShareCtx, global_help},
+        album::open_album,
+        app::{ShareCtx, Wrap, global_help},
+        artist::open_artist,
+        comment::open_comments,
         focus::Focus,
+        similar::open_similar,
         widgets::{help::Help, play_list::PlayList as PlayListWidget, tip::Msg},
         zero::Zero,
     },
@@ -30,8 +34,109 @@ use crate::{
 pub struct Lyric {
     pub text: String,
     pub duration: u16,
+    /// 同一时间的翻译与音译
+    pub trans: Option<String>,
+    pub roma: Option<String>,
 }
 
+impl Lyric {
+    pub fn sub(&self, sub: LyricSub) -> Option<&str> {
+        match sub {
+            LyricSub::None => None,
+            LyricSub::Trans => self.trans.as_deref(),
+            LyricSub::Roma => self.roma.as_deref(),
+        }
+        .filter(|v| !v.is_empty())
+    }
+}
+
+/// 歌词下方附带的内容, y 切换
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LyricSub {
+    None,
+    Trans,
+    Roma,
+}
+
+impl LyricSub {
+    pub fn next(self) -> Self {
+        match self {
+            LyricSub::None => LyricSub::Trans,
+            LyricSub::Trans => LyricSub::Roma,
+            LyricSub::Roma => LyricSub::None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LyricSub::None => "歌词: 原文",
+            LyricSub::Trans => "歌词: 翻译",
+            LyricSub::Roma => "歌词: 音译",
+        }
+    }
+}
+
+/// 翻译与原文时间差在这之内(毫秒)算同一行
+const LYRIC_SLOP: u32 = 50;
+
+/// 解析 lrc 的每一行, 时间为毫秒
+fn parse_lrc(lrc: &str) -> Vec<(u32, String)> {
+    let mut ret = vec![];
+    for line in lrc.split('\n') {
+        if !line.starts_with('[') {
+            continue;
+        }
+        let Some(r_index) = line.find(']') else {
+            continue;
+        };
+        let mut dt = 0_u32;
+        for (num, v) in line[1..r_index].split(':').enumerate() {
+            match num {
+                0 => {
+                    dt += v.parse().unwrap_or(0) * 60_000;
+                }
+                1 => {
+                    let (sec, frac) = v.split_once('.').unwrap_or((v, ""));
+                    dt += sec.parse().unwrap_or(0) * 1000;
+                    // .5 .50 .500 都是 500 毫秒
+                    let frac = format!("{:0<3}", frac.chars().take(3).collect::<String>());
+                    dt += frac.parse().unwrap_or(0);
+                }
+                _ => {}
+            }
+        }
+        ret.push((dt, line[r_index + 1..].to_owned()));
+    }
+    ret
+}
+
+/// 原文按时间合并翻译与音译
+pub fn parse_lyric(lyric: &typ::Lyric) -> Vec<Lyric> {
+    let extra = |v: &Option<typ::LyricInner>| -> BTreeMap<u32, String> {
+        v.as_ref()
+            .map(|v| parse_lrc(&v.lyric).into_iter().collect())
+            .unwrap_or_default()
+    };
+    let (trans, roma) = (extra(&lyric.tlyric), extra(&lyric.romalrc));
+    let near = |m: &BTreeMap<u32, String>, dt: u32| {
+        m.range(dt.saturating_sub(LYRIC_SLOP)..=dt + LYRIC_SLOP)
+            .min_by_key(|(k, _)| k.abs_diff(dt))
+            .map(|(_, v)| v.trim().to_owned())
+    };
+    parse_lrc(&lyric.lrc.lyric)
+        .into_iter()
+        .map(|(dt, text)| Lyric {
+            trans: near(&trans, dt),
+            roma: near(&roma, dt),
+            duration: (dt / 1000) as u16,
+            text,
+        })
+        .collect()
+}
+
+/// 私人 FM/心动模式剩下这么多首时去拉下一批
+const MORE_LOW: usize = 1;
+
 pub fn add_music_to_play(ctx: ShareCtx, id: usize) {
         let au = ctx.borrow().async_clone();
         ctx.borrow_mut().add_modal(PlayListWidget::new(
@@ -46,7 +151,12 @@ pub fn add_music_to_play(ctx: ShareCtx, id: usize) {
                                 Ok(e) => {
                                     aux.nc.clear_play_list(pid);
 
-                                    let resp = aux.nc.play_detail(pid).await;
+                                    let resp = aux
+                                        .nc
+                                        .play_detail(pid, &|d| {
+                                            aux.tx.send(ES::DataPlayListDetail(d));
+                                        })
+                                        .await;
                                     match resp {
                                         Ok(list) => {
                                             aux.tx.send(ES::DataPlayListDetail(list));
@@ -71,7 +181,7 @@ pub fn add_music_to_play(ctx: ShareCtx, id: usize) {
             let txx = ctx.borrow().tx.clone();
             let ncx = ctx.borrow().nc.clone();
             async move {
-                match ncx.play_list(0, 1000).await {
+                match ncx.play_list_all().await {
                     Ok(d) => {
                         txx.send(ES::DataPlayList(d));
                     }
@@ -83,6 +193,34 @@ pub fn add_music_to_play(ctx: ShareCtx, id: usize) {
         });
 }
 
+/// 切换喜欢状态, like_set 先改, 失败了再改回来
+pub fn toggle_like(ctx: ShareCtx, id: usize) {
+    let like = !ctx.borrow().like_set.contains(&id);
+    ctx.borrow().tx.send(ES::Liked(id, like));
+    let aux = ctx.borrow().async_clone();
+    let like_play_id = ctx.borrow().like_play_id;
+    ctx.borrow().rt.spawn(async move {
+        match aux.nc.like(id, like).await {
+            Ok(_) => {
+                // 喜欢的歌单下次打开时重新拉, 还没拿到歌单 id 时没有缓存要清
+                if like_play_id != 0
+                    && let Err(e) = aux.nc.clear_play_list(like_play_id)
+                {
+                    aux.tx.wrap_error("clear_play_list", &e);
+                }
+                aux.tx.send(ES::Tip(Msg(
+                    if like { "已喜欢" } else { "已取消喜欢" },
+                    Duration::from_millis(1500),
+                )));
+            }
+            Err(e) => {
+                aux.tx.send(ES::Liked(id, !like));
+                aux.tx.wrap_error("like", &e);
+            }
+        }
+    });
+}
+
 pub struct Footer {
     state: PlayState,
     current: Option<PlayItem>,
@@ -98,6 +236,12 @@ pub struct Footer {
     lyrics: Vec<Lyric>,
     bad: HashSet<usize>,
     played: HashSet<usize>,
+    // 正在下载的歌曲: id, 已下载, 总大小
+    download: Option<(usize, u64, Option<u64>)>,
+    // 正在拉取私人 FM/心动模式的下一批
+    more_loading: bool,
+    // 列表放完了, 等下一批拉到后接着放
+    more_wait: bool,
 }
 
 impl Footer {
@@ -118,19 +262,129 @@ impl Footer {
             bad: HashSet::new(),
             ctx: ctx,
             played: HashSet::new(),
+            download: None,
+            more_loading: false,
+            more_wait: false,
+        }
+    }
+
+    /// 选中的歌曲, 没选中时取正在播放的
+    fn selected_track(&self) -> Option<&PlayItem> {
+        self.list_state
+            .selected()
+            .and_then(|v| self.list.get(v))
+            .or(self.current.as_ref())
+    }
+
+    fn download_tip(&self) -> String {
+        match self.download {
+            Some((_, got, Some(total))) if total > 0 => {
+                format!("下载中 {}% ", got * 100 / total)
+            }
+            Some((_, got, _)) => format!("下载中 {:.1}M ", got as f64 / 1048576.0),
+            None => "".to_owned(),
         }
     }
+
     fn set_play_mode(&mut self, mode: PlayMode) {
+        let was_fm = matches!(self.play_mode, PlayMode::Fm);
         self.play_mode = mode;
         self.played.clear();
+        self.more_wait = false;
         match self.play_mode {
             PlayMode::Random => {
                 fastrand::shuffle(&mut self.list);
             }
+            PlayMode::Fm => {
+                if !was_fm || self.list.is_empty() {
+                    // 换成 FM 的列表, 拉到后马上播放; 上次没拉到时重新进入也走这里
+                    self.list.clear();
+                    self.bad.clear();
+                    self.list_state.select(None);
+                    self.more_wait = true;
+                    self.more_loading = false;
+                    self.load_more();
+                } else if self
+                    .list_state
+                    .selected()
+                    .is_some_and(|v| v + 1 >= self.list.len())
+                {
+                    // 放到队尾没拉到新歌, 重新进入时接着拉
+                    self.more_wait = true;
+                    self.load_more();
+                }
+            }
+            PlayMode::Intelligence(..) => {
+                // 种子歌曲由调用方放进列表播放, 这里只拉推荐
+                self.list.clear();
+                self.bad.clear();
+                self.list_state.select(None);
+                self.more_loading = false;
+                self.load_more();
+            }
             _ => {}
         }
     }
 
+    /// 私人 FM 与心动模式拉下一批
+    fn load_more(&mut self) {
+        if self.more_loading {
+            return;
+        }
+        let mode = self.play_mode.clone();
+        // 心动模式以队尾的歌继续推荐
+        let last = self.list.last().map(|v| v.id);
+        self.more_loading = true;
+        self.ctx.borrow().rt.spawn({
+            let aux = self.ctx.borrow().async_clone();
+            async move {
+                match mode {
+                    PlayMode::Fm => match aux.nc.fm().await {
+                        Ok(d) => {
+                            aux.tx.send(ES::DataFm(d));
+                        }
+                        Err(e) => {
+                            aux.tx.send(ES::DataFm(PersonalFm { data: vec![] }));
+                            aux.tx.wrap_error("fm", &e);
+                        }
+                    },
+                    PlayMode::Intelligence(pid, seed) => {
+                        match aux.nc.intelligence(pid, last.unwrap_or(seed)).await {
+                            Ok(d) => {
+                                aux.tx.send(ES::DataIntelligence(pid, d));
+                            }
+                            Err(e) => {
+                                aux.tx
+                                    .send(ES::DataIntelligence(pid, Intelligence { data: vec![] }));
+                                aux.tx.wrap_error("intelligence", &e);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// 续上拉到的一批, 列表已经放完时接着播放
+    fn queue_more(&mut self, items: Vec<PlayItem>) {
+        let start = self.list.len();
+        for v in items {
+            if !self.list.iter().any(|vv| vv.id == v.id) {
+                self.list.push(v);
+            }
+        }
+        if self.more_wait {
+            // 拉取失败或没有新歌时不再干等, 提示后停下
+            self.more_wait = false;
+            if self.list.len() > start {
+                self.ctx.borrow().ptx.send(PlayReq::Play(self.list[start].id));
+            } else {
+                self.ctx.borrow_mut().info("没有拉到新歌, 可重新进入重试");
+            }
+        }
+    }
+
     fn play_next(&mut self, first: bool) {
         if self.list.is_empty() {
             return;
@@ -141,6 +395,16 @@ impl Footer {
             PlayMode::SingleLoop => {
                 index = self.list_state.selected().unwrap_or(0);
             }
+            PlayMode::Fm | PlayMode::Intelligence(..) => {
+                index = self.list_state.selected().map(|v| v + 1).unwrap_or(0);
+                if self.list.len().saturating_sub(index) <= MORE_LOW {
+                    self.load_more();
+                }
+                if index >= self.list.len() {
+                    self.more_wait = true;
+                    return;
+                }
+            }
             _ => {
                 if first {
                     index = 0;
@@ -197,40 +461,15 @@ impl Footer {
                     self.state = s.clone();
                     match s {
                         PlayState::Failed(id) => {
+                            self.download = None;
                             self.bad.insert(*id);
                             self.play_next(false);
                         }
                         PlayState::Play(id, lyric) => {
+                            self.download = None;
                             self.bad.remove(id);
                             self.played.insert(*id);
-                            self.lyrics.clear();
-                            for line in lyric.split('\n') {
-                                if let Some(index) = line.find('[') {
-                                    if index == 0 {
-                                        if let Some(rIndex) = line.find(']') {
-                                            let dts = &line[index + 1..rIndex];
-                                            let mut dt = 0_u16;
-                                            for (num, v) in dts.split(':').enumerate() {
-                                                match num {
-                                                    0 => {
-                                                        dt += v.parse().unwrap_or(0) * 60;
-                                                    }
-                                                    1 => {
-                                                        dt += v[..v.find('.').unwrap_or(v.len())]
-                                                            .parse()
-                                                            .unwrap_or(0);
-                                                    }
-                                                    _ => {}
-                                                }
-                                            }
-                                            self.lyrics.push(Lyric {
-                                                text: line[rIndex + 1..].to_owned(),
-                                                duration: dt,
-                                            });
-                                        }
-                                    }
-                                }
-                            }
+                            self.lyrics = parse_lyric(lyric);
                             self.ctx
                                 .borrow()
                                 .tx
@@ -275,6 +514,33 @@ impl Footer {
                 }
                 _ => {}
             },
+            ES::DataFm(d) => {
+                // 拉取期间换了模式时结果直接丢掉, 但要放开下一次拉取
+                self.more_loading = false;
+                if matches!(self.play_mode, PlayMode::Fm) {
+                    let items = std::mem::take(&mut d.data);
+                    self.queue_more(items.into_iter().map(|v| v.into()).collect());
+                }
+            }
+            ES::DataIntelligence(pid, d) => {
+                self.more_loading = false;
+                if let PlayMode::Intelligence(v, seed) = self.play_mode
+                    && v == *pid
+                {
+                    let items = std::mem::take(&mut d.data);
+                    // 推荐里可能带着种子歌曲本身, 它已经在放了
+                    self.queue_more(
+                        items
+                            .into_iter()
+                            .filter(|v| v.id != seed)
+                            .filter_map(|v| v.song_info)
+                            .collect(),
+                    );
+                }
+            }
+            ES::Download(id, got, total) => {
+                self.download = Some((*id, *got, *total));
+            }
             ES::SEC => match self.state {
                 PlayState::Start => {
                     let offset = Duration::from_secs(self.current.as_ref().unwrap().dt / 1000);
@@ -303,6 +569,11 @@ impl Footer {
                         if self.plFocus.is_me() {
                             let mut base = global_help(vec![
                                 ("t".to_owned(), "加入歌单".to_owned()),
+                                ("g".to_owned(), "查看歌手".to_owned()),
+                                ("b".to_owned(), "查看专辑".to_owned()),
+                                ("w".to_owned(), "相似歌曲/歌单".to_owned()),
+                                ("c".to_owned(), "歌曲评论".to_owned()),
+                                ("l".to_owned(), "喜欢/取消喜欢".to_owned()),
                                 ("j/k".to_owned(), "下/上移动".to_owned()),
                                 ("enter".to_owned(), "播放选中歌曲".to_owned()),
                                 ("r".to_owned(), "随机播放列表".to_owned()),
@@ -323,6 +594,67 @@ impl Footer {
                             add_music_to_play(self.ctx.clone(), id);
                         }
                     }
+                    KeyCode::Char('g') => {
+                        if self.plFocus.is_me() {
+                            if let Some(v) = self.selected_track() {
+                                open_artist(self.ctx.clone(), v);
+                            }
+                        }
+                    }
+                    KeyCode::Char('c') => {
+                        if self.plFocus.is_me() {
+                            if let Some(v) = self.selected_track() {
+                                open_comments(
+                                    self.ctx.clone(),
+                                    CommentType::Song,
+                                    v.id,
+                                    v.name.as_str(),
+                                );
+                            }
+                        }
+                    }
+                    KeyCode::Char('w') => {
+                        if self.plFocus.is_me() {
+                            if let Some(v) = self.selected_track() {
+                                open_similar(self.ctx.clone(), v);
+                            }
+                        }
+                    }
+                    KeyCode::Char('b') => {
+                        if self.plFocus.is_me() {
+                            if let Some(v) = self.selected_track() {
+                                open_album(self.ctx.clone(), v);
+                            }
+                        }
+                    }
+                    KeyCode::Char('l') => {
+                        if self.plFocus.is_me() {
+                            if let Some(v) = self.selected_track() {
+                                toggle_like(self.ctx.clone(), v.id);
+                            }
+                        }
+                    }
+                    KeyCode::Char('y') => {
+                        let sub = self.ctx.borrow().lyric_sub.next();
+                        self.ctx.borrow_mut().lyric_sub = sub;
+                        self.ctx.borrow_mut().info(sub.name());
+                    }
+                    KeyCode::Char('X') => {
+                        if let (PlayMode::Fm, Some(v)) = (&self.play_mode, self.current.as_ref()) {
+                            let id = v.id;
+                            self.ctx.borrow().rt.spawn({
+                                let aux = self.ctx.borrow().async_clone();
+                                async move {
+                                    if let Err(e) = aux.nc.fm_trash(id).await {
+                                        aux.tx.wrap_error("fm_trash", &e);
+                                    }
+                                }
+                            });
+                            self.list.retain(|v| v.id != id);
+                            self.list_state.select(self.list_index.checked_sub(1));
+                            self.play_next(false);
+                        }
+                    }
                     KeyCode::Char('p') => {
                         self.list_view = !self.list_view;
                         if self.list_view {
@@ -442,11 +774,12 @@ impl Footer {
     ) {
         match self.state {
             PlayState::None => {
-                "-".render_ref(area, buf);
+                format!("{}-", self.download_tip()).render_ref(area, buf);
             }
             PlayState::Start | PlayState::Stop => {
                 format!(
-                    "{} {}/{} {} {} 音量 {}% {}",
+                    "{}{} {}/{} {} {} 音量 {}% {}",
+                    self.download_tip(),
                     if matches!(self.state, PlayState::Start) {
                         "playing..."
                     } else {
@@ -477,21 +810,29 @@ impl Footer {
                         PlayMode::SingleLoop => "单曲循环",
                         PlayMode::Random => "随机",
                         PlayMode::Single => "单曲",
+                        PlayMode::Fm => "私人FM",
+                        PlayMode::Intelligence(..) => "心动模式",
                         _ => "列表循环",
                     },
                     (self.volume * 100 as f32).ceil(),
                     if self.ctx.borrow().config.less_usage {
                         ""
                     } else {
-                        Self::get_lyric(self.offset.as_secs() as u16, self.lyrics.as_slice())
-                            .map(|v| self.lyrics[v].text.as_str())
-                            .unwrap_or("")
+                        &Self::get_lyric(self.offset.as_secs() as u16, self.lyrics.as_slice())
+                            .map(|v| {
+                                let v = &self.lyrics[v];
+                                match v.sub(self.ctx.borrow().lyric_sub) {
+                                    Some(sub) => format!("{} / {}", v.text, sub),
+                                    None => v.text.to_owned(),
+                                }
+                            })
+                            .unwrap_or_default()
                     },
                 )
                 .render(area, buf);
             }
             _ => {
-                "stop...".render(area, buf);
+                format!("{}stop...", self.download_tip()).render(area, buf);
             }
         }
 
@@ -545,3 +886,37 @@ impl Footer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lrc(lyric: &str) -> Option<typ::LyricInner> {
+        Some(typ::LyricInner {
+            lyric: lyric.to_owned(),
+        })
+    }
+
+    #[test]
+    fn merge_lyric() {
+        let lyric = typ::Lyric {
+            code: 200,
+            lrc: typ::LyricInner {
+                lyric: "[00:00.00]作词 : 某人\n[00:12.5]君の名は\n[01:02.30]ありがとう\n".to_owned(),
+            },
+            // 时间精度不同, 或差几毫秒也能对上
+            tlyric: lrc("[00:12.500]你的名字\n[01:02.31]谢谢\n"),
+            romalrc: lrc("[00:12.50]kimi no na wa\n"),
+        };
+        let lines = parse_lyric(&lyric);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].duration, 12);
+        assert_eq!(lines[2].duration, 62);
+        assert_eq!(lines[0].sub(LyricSub::Trans), None);
+        assert_eq!(lines[1].sub(LyricSub::Trans), Some("你的名字"));
+        assert_eq!(lines[1].sub(LyricSub::Roma), Some("kimi no na wa"));
+        assert_eq!(lines[1].sub(LyricSub::None), None);
+        assert_eq!(lines[2].sub(LyricSub::Trans), Some("谢谢"));
+        assert_eq!(lines[2].sub(LyricSub::Roma), None);
+    }
+}
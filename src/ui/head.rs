@@ -6,7 +6,7 @@ use ratatui::{
 };
 
 use crate::{
-    event::{ES, HeadMenuKey},
+    event::{ES, HeadMenuKey, Play, PlayMode},
     ui::{
         app::{ShareCtx, Wrap, global_help},
         focus::Focus,
@@ -58,16 +58,12 @@ impl WidgetRef for Head {
             .split(inner);
         b.render(area, buf);
         p.render_ref(layout[0], buf);
+        let mut cons = vec![Constraint::Length(4)];
+        cons.extend(self.list.iter().map(|_| Constraint::Fill(1)));
+        cons.push(Constraint::Length(4));
         let mods = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints(vec![
-                Constraint::Length(4),
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-                Constraint::Length(4),
-            ])
+            .constraints(cons)
             .split(layout[1]);
         "<-".render(mods[0], buf);
         self.list.iter().enumerate().for_each(|(index, v)| {
@@ -82,7 +78,7 @@ impl WidgetRef for Head {
             }))
             .render(mods[index + 1], buf);
         });
-        "->".render(mods[5], buf);
+        "->".render(mods[self.list.len() + 1], buf);
     }
 }
 
@@ -103,6 +99,14 @@ impl Head {
                     Title: "推荐".to_owned(),
                     Key: HeadMenuKey::Maybe,
                 },
+                HEAD_MENU {
+                    Title: "发现".to_owned(),
+                    Key: HeadMenuKey::Discover,
+                },
+                HEAD_MENU {
+                    Title: "私人FM".to_owned(),
+                    Key: HeadMenuKey::Fm,
+                },
                 HEAD_MENU {
                     Title: "关于".to_owned(),
                     Key: HeadMenuKey::About,
@@ -113,6 +117,14 @@ impl Head {
     }
 
     pub fn change_module(&mut self, key: HeadMenuKey) {
+        if let HeadMenuKey::Fm = key {
+            // FM 只是一种播放方式, 留在顶部
+            self.ctx
+                .borrow()
+                .tx
+                .send(ES::Play(Play::PlayMode(PlayMode::Fm)));
+            return;
+        }
         self.focus.set("slide");
         self.ctx.borrow().tx.send(ES::RuntimeHead(key.clone()));
 
@@ -121,7 +133,7 @@ impl Head {
                 self.ctx.borrow().rt.spawn({
                     let ctx = self.ctx.borrow().async_clone();
                     async move {
-                        match ctx.nc.play_list(0, 10).await {
+                        match ctx.nc.play_list_all().await {
                             Ok(vv) => {
                                 ctx.tx.send(ES::DataPlayList(vv));
                             }
@@ -143,6 +155,21 @@ impl Head {
                     }
                 });
             }
+            HeadMenuKey::Discover => {
+                self.ctx.borrow().rt.spawn({
+                    let ctx = self.ctx.borrow().async_clone();
+                    async move {
+                        match tokio::join!(ctx.nc.top_list(), ctx.nc.new_albums()) {
+                            (Ok(top), Ok(albums)) => {
+                                ctx.tx.send(ES::DataDiscover(top, albums));
+                            }
+                            (Err(err), _) | (_, Err(err)) => {
+                                ctx.tx.wrap_error("req discover", &err)
+                            }
+                        }
+                    }
+                });
+            }
             _ => {}
         }
     }
@@ -167,7 +194,13 @@ impl Head {
                             }
                             KeyCode::Char('j') => {
                                 self.pos += 1;
-                                if self.pos > 1 {
+                                // 关于不进入, 到它之前就绕回开头
+                                let end = self
+                                    .list
+                                    .iter()
+                                    .position(|v| matches!(v.Key, HeadMenuKey::About))
+                                    .unwrap_or(self.list.len());
+                                if self.pos >= end {
                                     self.pos = 0;
                                 }
                             }
@@ -177,8 +210,12 @@ impl Head {
                                 }
                             }
                             KeyCode::Enter => {
-                                self.index = self.pos;
-                                self.change_module(self.list[self.index].Key.clone());
+                                let key = self.list[self.pos].Key.clone();
+                                // 私人FM 不切换模块, 高亮留在当前模块上
+                                if !matches!(key, HeadMenuKey::Fm) {
+                                    self.index = self.pos;
+                                }
+                                self.change_module(key);
                                 return false;
                             }
                             _ => {}
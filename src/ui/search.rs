@@ -6,41 +6,359 @@ use ratatui::{
     layout::{Constraint, Direction, Layout},
     style::{Modifier, Style, palette::tailwind::SLATE},
     text::Line,
-    widgets::{Block, Clear, List, ListItem, ListState, StatefulWidgetRef, Widget, WidgetRef},
+    widgets::{
+        Block, Clear, List, ListItem, ListState, StatefulWidgetRef, Tabs, Widget, WidgetRef,
+    },
 };
 
 use crate::{
-    event::{Play, PlayListOP},
-    m163::typ::{PlayItem, PlayListItem},
+    event::{ES, Play, PlayListOP},
+    m163::typ::{PlayItem, PlayListItem, SearchResultInner, SearchType},
     play::PlayReq,
     ui::{
-        app::{Modal, ShareCtx, global_help},
+        album::{Album, open_album},
+        app::{Modal, ShareCtx, Wrap, global_help},
+        artist::{Artist, open_artist},
+        footer::toggle_like,
+        similar::open_similar,
         widgets::{help::Help, input::Input},
     },
 };
 
+/// 每页条数
+const PAGE: usize = 30;
+
+const TABS: [(SearchType, &str); 6] = [
+    (SearchType::Song, "单曲"),
+    (SearchType::Album, "专辑"),
+    (SearchType::Artist, "歌手"),
+    (SearchType::PlayList, "歌单"),
+    (SearchType::User, "用户"),
+    (SearchType::Lyric, "歌词"),
+];
+
+/// 输入时的建议项
+enum Suggest {
+    Keyword(String),
+    Artist(String),
+    Song(PlayItem),
+}
+
 pub struct Search {
-    list: Vec<PlayItem>,
+    result: SearchResultInner,
     list_state: ListState,
     close: bool,
     ctx: ShareCtx,
     list_op: bool,
     input: Input,
+    tab: usize,
+    keyword: String,
+    loading: bool,
+    suggest: Vec<Suggest>,
+    suggest_state: ListState,
+    // 在建议列表里选择
+    suggest_op: bool,
+    // 输入有变化, 下一次 SEC 时请求建议
+    typed: bool,
 }
 
 impl Search {
     pub fn new(ctx: ShareCtx) -> Self {
         Search {
-            list: vec![],
+            result: SearchResultInner::default(),
             list_state: ListState::default(),
             close: false,
             ctx: ctx.clone(),
             list_op: false,
             input: Input::new(ctx, "条件", |_: String| {}),
+            tab: 0,
+            keyword: String::new(),
+            loading: false,
+            suggest: vec![],
+            suggest_state: ListState::default(),
+            suggest_op: false,
+            typed: false,
+        }
+    }
+
+    fn load_suggest(&mut self) {
+        let s = self.input.to_string();
+        if s.trim().is_empty() {
+            self.suggest.clear();
+            return;
+        }
+        self.ctx.borrow().rt.spawn({
+            let aux = self.ctx.borrow().async_clone();
+            async move {
+                // 建议失败不打扰输入
+                if let Ok(d) = aux.nc.search_suggest(s.as_str()).await {
+                    aux.tx.send(ES::DataSuggest(s, d));
+                }
+            }
+        });
+    }
+
+    fn pick_suggest(&mut self) {
+        let Some(index) = self
+            .suggest_state
+            .selected()
+            .filter(|v| *v < self.suggest.len())
+        else {
+            return;
+        };
+        self.suggest_op = false;
+        match self.suggest.swap_remove(index) {
+            Suggest::Keyword(v) => {
+                self.input.set(&v);
+            }
+            Suggest::Artist(v) => {
+                self.input.set(&v);
+                self.tab = TABS
+                    .iter()
+                    .position(|v| v.0 == SearchType::Artist)
+                    .unwrap_or(0);
+            }
+            Suggest::Song(item) => {
+                let id = item.id;
+                self.ctx
+                    .borrow()
+                    .tx
+                    .send(ES::Play(Play::PlayList((vec![item], PlayListOP::Append))));
+                self.ctx.borrow().ptx.send(PlayReq::Play(id));
+                self.close = true;
+                return;
+            }
         }
+        self.suggest.clear();
+        self.keyword = self.input.to_string();
+        self.result = SearchResultInner::default();
+        self.load(0);
+    }
+
+    fn kind(&self) -> SearchType {
+        TABS[self.tab].0
+    }
+
+    fn len(&self) -> usize {
+        match self.kind() {
+            SearchType::Song | SearchType::Lyric => self.result.songs.len(),
+            SearchType::Album => self.result.albums.len(),
+            SearchType::Artist => self.result.artists.len(),
+            SearchType::PlayList => self.result.playlists.len(),
+            SearchType::User => self.result.users.len(),
+        }
+    }
+
+    /// 歌曲类结果里选中的那首
+    fn selected_track(&self) -> Option<&PlayItem> {
+        if !self.list_op || !matches!(self.kind(), SearchType::Song | SearchType::Lyric) {
+            return None;
+        }
+        self.list_state
+            .selected()
+            .and_then(|v| self.result.songs.get(v))
+            .map(|v| &v.song)
+    }
+
+    fn total(&self) -> usize {
+        (match self.kind() {
+            SearchType::Song | SearchType::Lyric => self.result.song_count,
+            SearchType::Album => self.result.album_count,
+            SearchType::Artist => self.result.artist_count,
+            SearchType::PlayList => self.result.playlist_count,
+            SearchType::User => self.result.user_count,
+        }) as usize
+    }
+
+    fn rows(&self) -> Vec<String> {
+        let r = &self.result;
+        match self.kind() {
+            SearchType::Song => r
+                .songs
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{} / {}{}",
+                        v.song.name,
+                        artist(&v.song),
+                        if self.ctx.borrow().like_set.contains(&v.song.id) {
+                            " 💗"
+                        } else {
+                            ""
+                        }
+                    )
+                })
+                .collect(),
+            SearchType::Lyric => r
+                .songs
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{} / {}  {}",
+                        v.song.name,
+                        artist(&v.song),
+                        v.lyrics.as_ref().map(lyric_snippet).unwrap_or_default()
+                    )
+                })
+                .collect(),
+            SearchType::Album => r
+                .albums
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{} / {} ({}首)",
+                        v.name,
+                        v.artist
+                            .as_ref()
+                            .and_then(|v| v.name.as_deref())
+                            .unwrap_or(""),
+                        v.size
+                    )
+                })
+                .collect(),
+            SearchType::Artist => r
+                .artists
+                .iter()
+                .map(|v| format!("{} ({}张专辑)", v.name, v.album_size))
+                .collect(),
+            SearchType::PlayList => r
+                .playlists
+                .iter()
+                .map(|v| format!("{} / {} ({}首)", v.name, v.creator.nickname, v.track_count))
+                .collect(),
+            SearchType::User => r
+                .users
+                .iter()
+                .map(|v| format!("{}  {}", v.nickname, v.signature.as_deref().unwrap_or("")))
+                .collect(),
+        }
+    }
+
+    fn load(&mut self, offset: usize) {
+        if self.keyword.is_empty() {
+            return;
+        }
+        self.loading = true;
+        let s = self.keyword.to_owned();
+        let kind = self.kind();
+        self.ctx.borrow().rt.spawn({
+            let aux = self.ctx.borrow().async_clone();
+            async move {
+                match aux.nc.search(s.as_str(), kind, offset, PAGE).await {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataSearch(s, kind, offset, d));
+                    }
+                    Err(e) => {
+                        aux.tx.send(ES::DataSearchFailed(s, kind, offset));
+                        aux.tx.wrap_error("search", &e);
+                    }
+                }
+            }
+        });
+    }
+
+    fn switch(&mut self, step: usize) {
+        self.tab = (self.tab + step) % TABS.len();
+        self.result = SearchResultInner::default();
+        self.list_state.select(None);
+        self.load(0);
+    }
+
+    fn merge(&mut self, r: SearchResultInner) {
+        let v = &mut self.result;
+        v.song_count = v.song_count.max(r.song_count);
+        v.album_count = v.album_count.max(r.album_count);
+        v.artist_count = v.artist_count.max(r.artist_count);
+        v.playlist_count = v.playlist_count.max(r.playlist_count);
+        v.user_count = v.user_count.max(r.user_count);
+        v.songs.extend(r.songs);
+        v.albums.extend(r.albums);
+        v.artists.extend(r.artists);
+        v.playlists.extend(r.playlists);
+        v.users.extend(r.users);
+    }
+
+    fn enter(&mut self) {
+        let Some(index) = self.list_state.selected().filter(|v| *v < self.len()) else {
+            return;
+        };
+        match self.kind() {
+            SearchType::Song | SearchType::Lyric => {
+                let item = self.result.songs[index].song.clone();
+                let id = item.id;
+                self.ctx
+                    .borrow()
+                    .tx
+                    .send(ES::Play(Play::PlayList((vec![item], PlayListOP::Append))));
+
+                self.ctx.borrow().ptx.send(PlayReq::Play(id));
+            }
+            SearchType::PlayList => {
+                let id = self.result.playlists[index].id;
+                self.ctx.borrow().rt.spawn({
+                    let aux = self.ctx.borrow().async_clone();
+                    async move {
+                        match aux
+                            .nc
+                            .play_detail(id, &|d| {
+                                aux.tx.send(ES::DataPlayListDetail(d));
+                            })
+                            .await
+                        {
+                            Ok(d) => {
+                                aux.tx.send(ES::DataPlayListDetail(d));
+                            }
+                            Err(e) => aux.tx.wrap_error("play_detail", &e),
+                        }
+                    }
+                });
+            }
+            SearchType::Album => {
+                let album = Album::new(self.ctx.clone(), self.result.albums[index].id);
+                self.ctx.borrow_mut().add_modal(album);
+                return;
+            }
+            SearchType::Artist => {
+                // 保留搜索结果, 从歌手页返回后还能继续选
+                let artist = Artist::new(self.ctx.clone(), self.result.artists[index].id);
+                self.ctx.borrow_mut().add_modal(artist);
+                return;
+            }
+            _ => {
+                self.ctx.borrow_mut().info("暂不支持");
+                return;
+            }
+        }
+        self.close = true;
     }
 }
 
+fn artist(v: &PlayItem) -> &str {
+    v.art_r
+        .first()
+        .and_then(|v| v.name.as_deref())
+        .unwrap_or("")
+}
+
+// 歌词搜索命中的片段, 可能是字符串数组也可能是 {txt}
+fn lyric_snippet(v: &serde_json::Value) -> String {
+    let text = match v {
+        serde_json::Value::Array(lines) => lines
+            .iter()
+            .filter_map(|v| v.as_str())
+            .find(|v| v.contains("<b>"))
+            .or(lines.first().and_then(|v| v.as_str()))
+            .unwrap_or(""),
+        serde_json::Value::Object(o) => o
+            .get("txt")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.lines().find(|v| !v.trim().is_empty()))
+            .unwrap_or(""),
+        _ => "",
+    };
+    text.replace("<b>", "").replace("</b>", "")
+}
+
 impl Modal for Search {
     fn render_ref(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
         let block = Block::bordered().title(Cow::Borrowed("搜索"));
@@ -60,6 +378,7 @@ impl Modal for Search {
         let ss = Layout::vertical(vec![
             Constraint::Length(3),
             Constraint::Length(1),
+            Constraint::Length(1),
             Constraint::Fill(1),
         ])
         .split(right);
@@ -67,46 +386,88 @@ impl Modal for Search {
         let input_area = block.inner(ss[0]);
         block.render_ref(ss[0], buf);
         self.input.render_inner(input_area, buf);
-        format!("共{}个结果", self.list.len()).render_ref(ss[1], buf);
+        Tabs::new(TABS.iter().map(|v| v.1))
+            .select(self.tab)
+            .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+            .render(ss[1], buf);
+        format!(
+            "共{}个结果{}",
+            self.total(),
+            if self.loading { " 加载中..." } else { "" }
+        )
+        .render_ref(ss[2], buf);
+        if !self.list_op && !self.suggest.is_empty() {
+            StatefulWidgetRef::render_ref(
+                &(List::new(
+                    self.suggest
+                        .iter()
+                        .map(|v| match v {
+                            Suggest::Keyword(v) => format!("[搜索] {}", v),
+                            Suggest::Artist(v) => format!("[歌手] {}", v),
+                            Suggest::Song(v) => format!("[单曲] {} / {}", v.name, artist(v)),
+                        })
+                        .map(|v| ListItem::new(Line::styled(v, Style::default())))
+                        .collect::<Vec<_>>(),
+                )
+                .block(Block::bordered().title(if self.suggest_op {
+                    "建议 j/k 选择, enter 确认"
+                } else {
+                    "建议 ↓ 选择"
+                }))
+                .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+                .highlight_symbol(">")),
+                ss[3],
+                buf,
+                &mut self.suggest_state,
+            );
+            return;
+        }
         StatefulWidgetRef::render_ref(
             &(List::new(
-                self.list
-                    .iter()
-                    .enumerate()
-                    .map(|(i, v)| {
-                        let line = Line::styled(
-                            format!(
-                                "{} / {}",
-                                v.name.as_str(),
-                                v.art_r
-                                    .first()
-                                    .unwrap()
-                                    .name
-                                    .as_ref()
-                                    .unwrap_or(&String::from(""))
-                            ),
-                            Style::default(),
-                        );
-
-                        ListItem::new(line)
-                    })
+                self.rows()
+                    .into_iter()
+                    .map(|v| ListItem::new(Line::styled(v, Style::default())))
                     .collect::<Vec<_>>(),
             )
             .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
             .highlight_symbol(">")),
-            ss[2],
+            ss[3],
             buf,
             &mut self.list_state,
         );
     }
 
     fn event(&mut self, e: &mut crate::event::ES) -> bool {
-        if !self.list_op {
+        if self.suggest_op {
+            if let crate::event::ES::Event(Event::Key(k)) = e {
+                match k.code {
+                    KeyCode::Char('j') | KeyCode::Down => self.suggest_state.select_next(),
+                    KeyCode::Char('k') | KeyCode::Up => self.suggest_state.select_previous(),
+                    KeyCode::Enter => self.pick_suggest(),
+                    KeyCode::Esc => self.suggest_op = false,
+                    _ => {}
+                }
+                return false;
+            }
+        } else if !self.list_op {
+            if let crate::event::ES::Event(Event::Key(k)) = e {
+                match k.code {
+                    KeyCode::Down if !self.suggest.is_empty() => {
+                        self.suggest_op = true;
+                        self.suggest_state.select_first();
+                        return false;
+                    }
+                    KeyCode::Char(_) | KeyCode::Backspace => {
+                        self.typed = true;
+                    }
+                    _ => {}
+                }
+            }
             self.input.event_inner(e);
             match e {
                 crate::event::ES::Event(ee) => match ee {
                     Event::Key(k) => match k.code {
-                        KeyCode::Enter | KeyCode::Esc => {}
+                        KeyCode::Enter | KeyCode::Esc | KeyCode::Tab | KeyCode::BackTab => {}
                         _ => {
                             return false;
                         }
@@ -117,14 +478,61 @@ impl Modal for Search {
             };
         }
         match e {
-            crate::event::ES::DataSearch(data) => {
-                self.list = data.result.songs.clone();
-
-                self.list_op = true;
-                if !self.list.is_empty() {
-                    self.list_state.select_first();
-                } else {
-                    self.list_state.select(None);
+            crate::event::ES::SEC => {
+                if self.typed && !self.list_op {
+                    self.typed = false;
+                    self.load_suggest();
+                }
+            }
+            crate::event::ES::DataSuggest(keyword, data) => {
+                // 只要当前输入对应的建议
+                if !self.list_op && *keyword == self.input.to_string() {
+                    let data = std::mem::take(data);
+                    self.suggest = data
+                        .keywords
+                        .into_iter()
+                        .map(Suggest::Keyword)
+                        .chain(
+                            data.artists
+                                .into_iter()
+                                .filter_map(|v| v.name)
+                                .map(Suggest::Artist),
+                        )
+                        .chain(data.songs.into_iter().map(|v| {
+                            Suggest::Song(PlayItem {
+                                name: v.name,
+                                id: v.id,
+                                dt: v.duration,
+                                art_r: v.artists,
+                                al: v.album,
+                            })
+                        }))
+                        .collect();
+                    self.suggest_state.select(None);
+                }
+            }
+            crate::event::ES::DataSearchFailed(keyword, kind, _) => {
+                if *keyword == self.keyword && *kind == self.kind() {
+                    self.loading = false;
+                }
+            }
+            crate::event::ES::DataSearch(keyword, kind, offset, data) => {
+                // 换了关键词或类型后, 旧的结果直接丢掉
+                if *keyword != self.keyword || *kind != self.kind() {
+                    return true;
+                }
+                self.loading = false;
+                let r = std::mem::take(&mut data.result);
+                if *offset == 0 {
+                    self.result = r;
+                    self.list_op = true;
+                    if self.len() > 0 {
+                        self.list_state.select_first();
+                    } else {
+                        self.list_state.select(None);
+                    }
+                } else if *offset == self.len() {
+                    self.merge(r);
                 }
             }
             crate::event::ES::Event(ee) => match ee {
@@ -142,13 +550,19 @@ impl Modal for Search {
                             ),
                             (
                                 "enter".to_owned(),
-                                if self.list_op { "播放" } else { "搜索" }.to_owned(),
+                                if self.list_op { "打开" } else { "搜索" }.to_owned(),
                             ),
+                            ("tab".to_owned(), "切换结果类型".to_owned()),
+                            ("↓".to_owned(), "选择输入建议".to_owned()),
                             ("q".to_owned(), "退出".to_owned()),
                         ];
 
                         if self.list_op {
-                            base.push(("j/k".to_owned(), "下/上移动".to_owned()));
+                            base.push(("j/k".to_owned(), "下/上移动, 到底加载更多".to_owned()));
+                            base.push(("g".to_owned(), "查看歌曲的歌手".to_owned()));
+                            base.push(("b".to_owned(), "查看歌曲的专辑".to_owned()));
+                            base.push(("w".to_owned(), "相似歌曲/歌单".to_owned()));
+                            base.push(("l".to_owned(), "喜欢/取消喜欢".to_owned()));
                         }
                         self.ctx
                             .borrow_mut()
@@ -158,8 +572,43 @@ impl Modal for Search {
                         self.list_state.select_previous();
                     }
                     KeyCode::Char('j') => {
+                        let at_end = self
+                            .list_state
+                            .selected()
+                            .is_some_and(|v| v + 1 >= self.len());
+                        if at_end && self.len() < self.total() && !self.loading {
+                            self.load(self.len());
+                        }
                         self.list_state.select_next();
                     }
+                    KeyCode::Char('l') => {
+                        if let Some(v) = self.selected_track() {
+                            toggle_like(self.ctx.clone(), v.id);
+                        }
+                    }
+                    KeyCode::Char('b') => {
+                        if let Some(v) = self.selected_track() {
+                            open_album(self.ctx.clone(), v);
+                        }
+                    }
+                    KeyCode::Char('w') => {
+                        if let Some(v) = self.selected_track() {
+                            open_similar(self.ctx.clone(), v);
+                        }
+                    }
+                    KeyCode::Char('g') => {
+                        if let Some(v) = self.selected_track() {
+                            open_artist(self.ctx.clone(), v);
+                        }
+                    }
+                    KeyCode::Tab => {
+                        self.switch(1);
+                        return false;
+                    }
+                    KeyCode::BackTab => {
+                        self.switch(TABS.len() - 1);
+                        return false;
+                    }
                     KeyCode::Esc => {
                         if self.list_op {
                             self.list_op = false;
@@ -170,33 +619,12 @@ impl Modal for Search {
                     }
                     KeyCode::Enter => {
                         if !self.list_op {
-                            let s = self.input.to_string();
-                            if !s.is_empty() {
-                                self.ctx.borrow().rt.spawn({
-                                    let aux = self.ctx.borrow().async_clone();
-                                    async move {
-                                        match aux.nc.search(s.as_str()).await {
-                                            Ok(d) => {
-                                                aux.tx.send(crate::event::ES::DataSearch(d));
-                                            }
-                                            Err(e) => println!("e {}", e.to_string()),
-                                        }
-                                    }
-                                });
-                            }
+                            self.suggest.clear();
+                            self.keyword = self.input.to_string();
+                            self.result = SearchResultInner::default();
+                            self.load(0);
                         } else {
-                            let item = self.list[self.list_state.selected().unwrap_or(0)].clone();
-                            let id = item.id;
-                            self.ctx
-                                .borrow()
-                                .tx
-                                .send(crate::event::ES::Play(Play::PlayList((
-                                    vec![item],
-                                    PlayListOP::Append,
-                                ))));
-
-                            self.ctx.borrow().ptx.send(PlayReq::Play(id));
-                            self.close = true;
+                            self.enter();
                         }
                         return false;
                     }
@@ -0,0 +1,322 @@
+use std::borrow::Cow;
+
+use ratatui::{
+    crossterm::event::{Event, KeyCode},
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style, palette::tailwind::SLATE},
+    text::Line,
+    widgets::{
+        Block, Clear, List, ListItem, ListState, StatefulWidgetRef, Tabs, Widget, WidgetRef,
+    },
+};
+
+use crate::{
+    event::{ES, Play, PlayListOP, PlayMode},
+    m163::typ::{PlayItem, SearchPlayList},
+    play::PlayReq,
+    ui::{
+        album::open_album,
+        app::{Modal, ShareCtx, Wrap, global_help},
+        artist::open_artist,
+        footer::{add_music_to_play, toggle_like},
+        widgets::help::Help,
+    },
+};
+
+const TABS: [&str; 2] = ["相似歌曲", "相似歌单"];
+
+/// 相似歌曲与歌单
+pub struct Similar {
+    seed: PlayItem,
+    songs: Vec<PlayItem>,
+    play_lists: Vec<SearchPlayList>,
+    loading: bool,
+    tab: usize,
+    list_state: ListState,
+    close: bool,
+    ctx: ShareCtx,
+}
+
+impl Similar {
+    pub fn new(ctx: ShareCtx, seed: PlayItem) -> Self {
+        let id = seed.id;
+        ctx.borrow().rt.spawn({
+            let aux = ctx.borrow().async_clone();
+            async move {
+                match aux.nc.simi_song(id).await {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataSimiSong(id, d));
+                    }
+                    Err(e) => aux.tx.wrap_error("simi_song", &e),
+                }
+                match aux.nc.simi_play_list(id).await {
+                    Ok(d) => {
+                        aux.tx.send(ES::DataSimiPlayList(id, d));
+                    }
+                    Err(e) => aux.tx.wrap_error("simi_play_list", &e),
+                }
+            }
+        });
+        Similar {
+            seed,
+            songs: vec![],
+            play_lists: vec![],
+            loading: true,
+            tab: 0,
+            list_state: ListState::default(),
+            close: false,
+            ctx,
+        }
+    }
+
+    fn len(&self) -> usize {
+        if self.tab == 0 {
+            self.songs.len()
+        } else {
+            self.play_lists.len()
+        }
+    }
+
+    fn selected_song(&self) -> Option<&PlayItem> {
+        if self.tab != 0 {
+            return None;
+        }
+        self.list_state.selected().and_then(|v| self.songs.get(v))
+    }
+
+    fn rows(&self) -> Vec<String> {
+        let ctx = self.ctx.borrow();
+        if self.tab == 0 {
+            self.songs
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{} - {}",
+                        ctx.maybe_hidden(v.name.as_str()),
+                        ctx.maybe_hidden(
+                            v.art_r
+                                .first()
+                                .and_then(|v| v.name.as_deref())
+                                .unwrap_or("")
+                        )
+                    )
+                })
+                .collect()
+        } else {
+            self.play_lists
+                .iter()
+                .map(|v| {
+                    format!(
+                        "{} ({}首) by {}",
+                        ctx.maybe_hidden(v.name.as_str()),
+                        v.track_count,
+                        ctx.maybe_hidden(v.creator.nickname.as_str())
+                    )
+                })
+                .collect()
+        }
+    }
+
+    /// 用种子歌曲加相似歌曲替换播放列表, 当作临时电台
+    fn radio(&self) {
+        if self.songs.is_empty() {
+            return;
+        }
+        let mut list = vec![self.seed.clone()];
+        list.extend(self.songs.iter().filter(|v| v.id != self.seed.id).cloned());
+        self.ctx
+            .borrow()
+            .tx
+            .send(ES::Play(Play::PlayMode(PlayMode::Order)));
+        self.ctx
+            .borrow()
+            .tx
+            .send(ES::Play(Play::PlayList((list, PlayListOP::Set))));
+        self.ctx.borrow().ptx.send(PlayReq::Play(self.seed.id));
+    }
+
+    fn enter(&mut self) {
+        let Some(index) = self.list_state.selected().filter(|v| *v < self.len()) else {
+            return;
+        };
+        if self.tab == 0 {
+            let item = self.songs[index].clone();
+            let id = item.id;
+            self.ctx
+                .borrow()
+                .tx
+                .send(ES::Play(Play::PlayList((vec![item], PlayListOP::Append))));
+            self.ctx.borrow().ptx.send(PlayReq::Play(id));
+        } else {
+            // 歌单在 Content 中打开
+            let id = self.play_lists[index].id;
+            self.ctx.borrow().rt.spawn({
+                let aux = self.ctx.borrow().async_clone();
+                async move {
+                    match aux
+                        .nc
+                        .play_detail(id, &|d| {
+                            aux.tx.send(ES::DataPlayListDetail(d));
+                        })
+                        .await
+                    {
+                        Ok(d) => {
+                            aux.tx.send(ES::DataPlayListDetail(d));
+                        }
+                        Err(e) => aux.tx.wrap_error("play_detail", &e),
+                    }
+                }
+            });
+            self.close = true;
+        }
+    }
+}
+
+/// 打开歌曲的相似歌曲页面
+pub fn open_similar(ctx: ShareCtx, item: &PlayItem) {
+    let similar = Similar::new(ctx.clone(), item.clone());
+    ctx.borrow_mut().add_modal(similar);
+}
+
+impl Modal for Similar {
+    fn render_ref(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let title = format!(
+            "相似: {}",
+            self.ctx.borrow().maybe_hidden(self.seed.name.as_str())
+        );
+        let block = Block::bordered().title(Cow::Owned(title));
+        let layouts = Layout::new(
+            Direction::Horizontal,
+            vec![
+                Constraint::Percentage(20),
+                Constraint::Fill(1),
+                Constraint::Percentage(20),
+            ],
+        )
+        .split(area);
+
+        let inner = block.inner(layouts[1]);
+        Clear.render(layouts[1], buf);
+        block.render(layouts[1], buf);
+        let ss = Layout::vertical(vec![Constraint::Length(1), Constraint::Fill(1)]).split(inner);
+        Tabs::new(TABS)
+            .select(self.tab)
+            .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+            .render(ss[0], buf);
+        if self.loading && self.len() == 0 {
+            "loading".render_ref(ss[1], buf);
+            return;
+        }
+        StatefulWidgetRef::render_ref(
+            &(List::new(
+                self.rows()
+                    .into_iter()
+                    .map(|v| ListItem::new(Line::styled(v, Style::default())))
+                    .collect::<Vec<_>>(),
+            )
+            .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">")),
+            ss[1],
+            buf,
+            &mut self.list_state,
+        );
+    }
+
+    fn event(&mut self, e: &mut crate::event::ES) -> bool {
+        match e {
+            ES::DataSimiSong(id, d) => {
+                if *id == self.seed.id {
+                    self.songs = std::mem::take(&mut d.songs)
+                        .into_iter()
+                        .map(|v| v.into())
+                        .collect();
+                    if self.tab == 0 && !self.songs.is_empty() {
+                        self.list_state.select_first();
+                    }
+                }
+            }
+            ES::DataSimiPlayList(id, d) => {
+                if *id == self.seed.id {
+                    self.loading = false;
+                    self.play_lists = std::mem::take(&mut d.playlists);
+                    if self.tab == 1 && !self.play_lists.is_empty() {
+                        self.list_state.select_first();
+                    }
+                }
+            }
+            ES::Event(Event::Key(k)) => match k.code {
+                KeyCode::Char('h') => {
+                    self.ctx.borrow_mut().add_modal(Help::new(global_help(vec![
+                        ("esc".to_owned(), "返回上一级".to_owned()),
+                        ("tab".to_owned(), "切换歌曲/歌单".to_owned()),
+                        ("j/k".to_owned(), "下/上移动".to_owned()),
+                        ("enter".to_owned(), "播放歌曲/打开歌单".to_owned()),
+                        ("t".to_owned(), "加入到某个歌单".to_owned()),
+                        ("g".to_owned(), "查看歌手".to_owned()),
+                        ("b".to_owned(), "查看专辑".to_owned()),
+                        ("l".to_owned(), "喜欢/取消喜欢".to_owned()),
+                        ("r".to_owned(), "以相似歌曲替换播放列表".to_owned()),
+                    ])));
+                    return false;
+                }
+                KeyCode::Char('k') => {
+                    self.list_state.select_previous();
+                    return false;
+                }
+                KeyCode::Char('j') => {
+                    self.list_state.select_next();
+                    return false;
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    self.tab = (self.tab + 1) % TABS.len();
+                    self.list_state
+                        .select(if self.len() > 0 { Some(0) } else { None });
+                    return false;
+                }
+                KeyCode::Char('t') => {
+                    if let Some(v) = self.selected_song() {
+                        add_music_to_play(self.ctx.clone(), v.id);
+                    }
+                    return false;
+                }
+                KeyCode::Char('g') => {
+                    if let Some(v) = self.selected_song() {
+                        open_artist(self.ctx.clone(), v);
+                    }
+                    return false;
+                }
+                KeyCode::Char('b') => {
+                    if let Some(v) = self.selected_song() {
+                        open_album(self.ctx.clone(), v);
+                    }
+                    return false;
+                }
+                KeyCode::Char('l') => {
+                    if let Some(v) = self.selected_song() {
+                        toggle_like(self.ctx.clone(), v.id);
+                    }
+                    return false;
+                }
+                KeyCode::Char('r') => {
+                    self.radio();
+                    return false;
+                }
+                KeyCode::Enter => {
+                    self.enter();
+                    return false;
+                }
+                KeyCode::Esc => {
+                    self.close = true;
+                    return false;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        true
+    }
+
+    fn closed(&self) -> bool {
+        self.close
+    }
+}
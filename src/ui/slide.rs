@@ -10,9 +10,9 @@ use rodio::cpal::FromSample;
 
 use crate::{
     event::{ES, HeadMenuKey},
-    m163::typ::{PlayList, PlayListItem},
+    m163::typ::{PlayDetail, PlayDetailInner, PlayList, PlayListItem},
     ui::{
-        app::{ShareCtx, global_help},
+        app::{ShareCtx, Wrap, global_help},
         focus::Focus,
         widgets::{help::Help, input::Input, tip::Msg},
     },
@@ -21,6 +21,10 @@ use crate::{
 pub struct Slide {
     list: Option<PlayList>,
     list_state: ListState,
+    // 分两组显示: 前 n 个与其余的标题, 如创建的/收藏的歌单
+    groups: Option<(usize, [&'static str; 2])>,
+    // 带分组标题的显示用状态
+    view_state: ListState,
     list_index: usize,
     focus: Focus,
     ctx: ShareCtx,
@@ -32,6 +36,8 @@ impl Slide {
         Slide {
             list: None,
             list_state: ListState::default(),
+            groups: None,
+            view_state: ListState::default(),
             list_index: 0,
             focus: focus,
             ctx: ctx,
@@ -47,6 +53,19 @@ impl Slide {
         }
     }
 
+    // 只有自己的歌单能删除/取消收藏
+    fn is_my(&self) -> bool {
+        matches!(self.runtime_head, HeadMenuKey::My)
+    }
+
+    // 第 i 个歌单在显示列表里的行, 跳过分组标题
+    fn row(&self, i: usize) -> usize {
+        match self.groups {
+            Some((n, _)) => i + 1 + (i >= n) as usize,
+            None => i,
+        }
+    }
+
     fn format_number(&self, num: usize) -> String {
         match num {
             0..=999 => num.to_string(),
@@ -68,12 +87,35 @@ impl Slide {
             if index >= self.list.as_ref().unwrap().list.len() {
                 return
             }
+            let discover = matches!(self.runtime_head, HeadMenuKey::Discover);
+            // 发现里第二组是新碟
+            let album = discover && self.groups.is_some_and(|(n, _)| index >= n);
             self.ctx.borrow().rt.spawn({
                 let nc = self.ctx.borrow().nc.clone();
                 let tx = self.ctx.borrow().tx.clone();
                 let id = self.list.as_ref().unwrap().list[index].id;
                 async move {
-                    if id == 0 {
+                    if album {
+                        match nc.album(id).await {
+                            Ok(d) => {
+                                tx.send(ES::DataPlayListDetail(d.into()));
+                            }
+                            Err(e) => tx.wrap_error("album", &e),
+                        }
+                    } else if id == 0 && discover {
+                        match nc.new_songs().await {
+                            Ok(d) => {
+                                tx.send(ES::DataPlayListDetail(PlayDetail {
+                                    playlist: PlayDetailInner {
+                                        name: "新歌速递".to_owned(),
+                                        tracks: d.data.into_iter().map(|v| v.into()).collect(),
+                                        ..Default::default()
+                                    },
+                                }));
+                            }
+                            Err(e) => tx.wrap_error("new_songs", &e),
+                        }
+                    } else if id == 0 {
                         let resp = nc.recommend_songs().await;
                         match resp {
                             Ok(list) => {
@@ -84,7 +126,11 @@ impl Slide {
                             }
                         }
                     } else {
-                        let resp = nc.play_detail(id).await;
+                        let resp = nc
+                            .play_detail(id, &|d| {
+                                tx.send(ES::DataPlayListDetail(d));
+                            })
+                            .await;
                         match resp {
                             Ok(list) => {
                                 tx.send(ES::DataPlayListDetail(list));
@@ -103,8 +149,15 @@ impl Slide {
         match e {
             ES::DataPlayList(pl) => {
                 if self.focus.is_me() {
-                    self.list = Some(pl.clone());
-                    self.ctx.borrow_mut().like_play_id = self.list.as_ref().unwrap().list[0].id;
+                    let (mut list, subscribed): (Vec<_>, Vec<_>) =
+                        pl.list.iter().cloned().partition(|v| !v.subscribed);
+                    self.groups = Some((list.len(), ["创建的歌单", "收藏的歌单"]));
+                    // 第一个创建的歌单是喜欢的音乐
+                    if let Some(v) = list.first() {
+                        self.ctx.borrow_mut().like_play_id = v.id;
+                    }
+                    list.extend(subscribed);
+                    self.list = Some(PlayList { more: false, list });
                     self.init();
                 }
             }
@@ -131,6 +184,51 @@ impl Slide {
                     });
                 });
                 self.list = Some(PlayList { more: false, list });
+                self.groups = None;
+                self.init();
+            }
+            ES::DataDiscover(top, albums) => {
+                // id 0 是新歌速递, 其余榜单都是歌单
+                let mut list: Vec<PlayListItem> = vec![PlayListItem {
+                    id: 0,
+                    subscribed: false,
+                    name: String::from("新歌速递"),
+                    cover_img_url: String::from(""),
+                    track_count: 0,
+                    play_count: 0,
+                    ordered: false,
+                }];
+                top.list.iter().for_each(|v| {
+                    list.push(PlayListItem {
+                        id: v.id,
+                        subscribed: false,
+                        name: format!("{} ({})", v.name, v.update_frequency),
+                        cover_img_url: v.cover_img_url.to_owned(),
+                        track_count: v.track_count,
+                        play_count: v.play_count,
+                        ordered: false,
+                    });
+                });
+                self.groups = Some((list.len(), ["榜单", "新碟上架"]));
+                albums.albums.iter().for_each(|v| {
+                    list.push(PlayListItem {
+                        id: v.id,
+                        subscribed: false,
+                        name: format!(
+                            "{} - {}",
+                            v.name,
+                            v.artist
+                                .as_ref()
+                                .and_then(|v| v.name.as_deref())
+                                .unwrap_or("")
+                        ),
+                        cover_img_url: String::from(""),
+                        track_count: v.size as usize,
+                        play_count: 0,
+                        ordered: false,
+                    });
+                });
+                self.list = Some(PlayList { more: false, list });
                 self.init();
             }
             ES::RuntimeHead(rh) => {
@@ -179,7 +277,7 @@ impl Slide {
 
                                                                 match ctx
                                                                     .nc
-                                                                    .play_list(0, 1000)
+                                                                    .play_list_all()
                                                                     .await
                                                                 {
                                                                     Ok(d) => {
@@ -204,7 +302,7 @@ impl Slide {
                                     ));
                                 }
                                 KeyCode::Char('x') => {
-                                    if let Some(index) = self.list_state.selected() {
+                                    if let Some(index) = self.list_state.selected().filter(|_| self.is_my()) {
                                         if !self.list.as_ref().unwrap().list[index].subscribed {
                                             let ncx = self.ctx.borrow().nc.clone();
                                             let txx = self.ctx.borrow().tx.clone();
@@ -224,7 +322,7 @@ impl Slide {
                                                                     Ok(_) => {
                                                                         t2x.send(ES::Tip(Msg("删除歌单成功!", Duration::from_secs(2))));
                                                                         n2x.clear_play();
-                                                                        match n2x.play_list(0, 1000).await {
+                                                                        match n2x.play_list_all().await {
                                                                             Ok(d) => {
                                                                                 t2x.send(ES::DataPlayList(
                                                                                     d,
@@ -249,7 +347,7 @@ impl Slide {
                                     }
                                 }
                                 KeyCode::Char('d') => {
-                                    if let Some(index) = self.list_state.selected() {
+                                    if let Some(index) = self.list_state.selected().filter(|_| self.is_my()) {
                                         if self.list.as_ref().unwrap().list[index].subscribed {
                                             let ncx = self.ctx.borrow().nc.clone();
                                             let txx = self.ctx.borrow().tx.clone();
@@ -269,7 +367,7 @@ impl Slide {
                                                                     Ok(_) => {
                                                                         t2x.send(ES::Tip(Msg("取消收藏成功!", Duration::from_secs(2))));
                                                                         n2x.clear_play();
-                                                                        match n2x.play_list(0, 1000).await {
+                                                                        match n2x.play_list_all().await {
                                                                             Ok(d) => {
                                                                                 t2x.send(ES::DataPlayList(
                                                                                     d,
@@ -307,6 +405,11 @@ impl Slide {
                                     } else {
                                         self.list_state.select_next();
                                     }
+                                    // 渲染用的是 view_state, 这里自己限制在范围内
+                                    let len = self.list.as_ref().map(|v| v.list.len()).unwrap_or(0);
+                                    if self.list_state.selected().is_some_and(|v| v >= len) {
+                                        self.list_state.select(len.checked_sub(1));
+                                    }
                                     self.load_list();
                                 }
                                 KeyCode::Esc => self.focus.back(),
@@ -337,38 +440,55 @@ impl Slide {
         let inner = b.inner(area);
         b.render(area, buf);
         match &self.list {
-            Some(pl) => StatefulWidgetRef::render_ref(
-                &(List::new(
-                    pl.list
-                        .iter()
-                        .enumerate()
-                        .map(|(i, v)| {
-                            let vv = format!(
-                                "{}{} {}",
-                                if self.list_index == i { "*" } else { "" },
-                                if v.play_count > 0 {
-                                    &format!("[{}]", self.format_number(v.play_count).as_str())
-                                } else {
-                                    ""
-                                },
-                                v.name.as_str()
-                            );
-                            let mut style = Style::default();
-                            if !v.subscribed {
-                                style = style.fg(ratatui::style::Color::Blue);
-                            }
-                            let line = Line::styled(self.ctx.borrow().maybe_hidden(vv.as_str()), style);
+            Some(pl) => {
+                let mut items = pl
+                    .list
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        let vv = format!(
+                            "{}{} {}",
+                            if self.list_index == i { "*" } else { "" },
+                            if v.play_count > 0 {
+                                &format!("[{}]", self.format_number(v.play_count).as_str())
+                            } else {
+                                ""
+                            },
+                            v.name.as_str()
+                        );
+                        let mut style = Style::default();
+                        if !v.subscribed {
+                            style = style.fg(ratatui::style::Color::Blue);
+                        }
+                        let line = Line::styled(self.ctx.borrow().maybe_hidden(vv.as_str()), style);
 
-                            ListItem::new(line)
-                        })
-                        .collect::<Vec<_>>(),
-                )
-                .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
-                .highlight_symbol(">")),
-                inner,
-                buf,
-                &mut self.list_state,
-            ),
+                        ListItem::new(line)
+                    })
+                    .collect::<Vec<_>>();
+                if let Some((n, names)) = self.groups {
+                    let title = Style::default().add_modifier(Modifier::DIM);
+                    items.insert(
+                        n,
+                        ListItem::new(Line::styled(
+                            format!("{}({})", names[1], pl.list.len() - n),
+                            title,
+                        )),
+                    );
+                    items.insert(
+                        0,
+                        ListItem::new(Line::styled(format!("{}({})", names[0], n), title)),
+                    );
+                }
+                self.view_state.select(self.list_state.selected().map(|v| self.row(v)));
+                StatefulWidgetRef::render_ref(
+                    &(List::new(items)
+                        .highlight_style(Style::new().bg(SLATE.c800).add_modifier(Modifier::BOLD))
+                        .highlight_symbol(">")),
+                    inner,
+                    buf,
+                    &mut self.view_state,
+                );
+            }
             None => "loading".render_ref(inner, buf),
         }
     }
@@ -41,6 +41,11 @@ impl Input {
     pub fn to_string(&self) -> String {
         String::from_iter(&self.v[..self.v.len() - 1])
     }
+
+    pub fn set(&mut self, v: &str) {
+        self.v = v.chars().collect();
+        self.v.push(if self.cursor { '|' } else { ' ' });
+    }
 }
 
 impl Input {
@@ -1,14 +1,14 @@
 use ratatui::{
     crossterm::event::{Event, KeyCode},
     layout::{Constraint, Direction, Layout},
-    style::Style,
-    text::Line,
+    style::{Modifier, Style},
+    text::{Line, Text},
     widgets::{Clear, List, ListItem, Widget, WidgetRef},
 };
 
 use crate::ui::{
     app::{Modal, ShareCtx, global_help},
-    footer::{Footer, Lyric},
+    footer::{Footer, Lyric, LyricSub},
     widgets::help::Help,
 };
 
@@ -38,12 +38,17 @@ impl Zero {
 
 impl Modal for Zero {
     fn render_ref(&mut self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let sub = self.ctx.borrow().lyric_sub;
         let mut high = VIEW_PRE_LINES * 2 + 1;
         let mut half = VIEW_PRE_LINES;
         if high > area.height as usize {
             high = area.height as usize;
             half = high / 2;
         }
+        if sub != LyricSub::None {
+            // 每句下面多一行, 前后少放一半
+            half /= 2;
+        }
         let layouts = Layout::vertical(vec![
             Constraint::Fill(1),
             Constraint::Min(high as u16),
@@ -99,7 +104,13 @@ impl Modal for Zero {
                     )
                     .centered();
 
-                    ListItem::new(line)
+                    match v.sub(sub) {
+                        Some(t) => ListItem::new(Text::from(vec![
+                            line,
+                            Line::styled(t, style.add_modifier(Modifier::DIM)).centered(),
+                        ])),
+                        None => ListItem::new(line),
+                    }
                 })
                 .collect::<Vec<_>>(),
         )
@@ -120,8 +131,14 @@ impl Modal for Zero {
 
                     let mut max = 0_u16;
                     d.iter().for_each(|v| {
-                        if v.text.len() as u16 > max {
-                            max = v.text.len() as u16;
+                        let len = [Some(&v.text), v.trans.as_ref(), v.roma.as_ref()]
+                            .into_iter()
+                            .flatten()
+                            .map(|v| v.len())
+                            .max()
+                            .unwrap_or(0);
+                        if len as u16 > max {
+                            max = len as u16;
                         }
                     });
                     self.max = max;